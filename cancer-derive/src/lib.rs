@@ -0,0 +1,254 @@
+use proc_macro::{Delimiter, TokenStream, TokenTree};
+
+/// Short identifiers reserved by help and version flags
+const RESERVED_SHORTS: [&str; 2] = ["h", "v"];
+
+/// A field of the derived struct
+struct Field {
+    /// Field name, e.g. `max_size`
+    name: String,
+    /// Field type without spaces, e.g. `Option<String>`
+    kind: String,
+    /// Description from doc comments
+    description: String,
+}
+
+/// Derive `cancer::flag::FromFlags` for a struct with named fields, each field becomes a flag
+///
+/// The long identifier is the field name with `_` replaced by `-`, the short identifier is the first character
+/// of the field name unless it is taken by a previous field or by help and version flags,
+/// doc comments of a field become the description of its flag
+///
+/// Supported field types are `bool`, `String`, `i32`, `f32`, `Vec<String>` which is split on `,`,
+/// and `Option` of `String`, `i32` and `f32` which is `None` when the flag is not set
+///
+/// # Example
+///
+/// ```ignore
+/// #[derive(cancer::Command)]
+/// struct Options {
+///     /// times to say hello
+///     count: i32,
+///     /// name to greet
+///     name: Option<String>,
+/// }
+/// ```
+#[proc_macro_derive(Command)]
+pub fn derive_command(input: TokenStream) -> TokenStream {
+    match derive(input) {
+        Ok(code) => code.parse().expect("failed parsing generated code"),
+        Err(message) => format!("compile_error!({:?});", message)
+            .parse()
+            .expect("failed parsing compile error"),
+    }
+}
+
+/// Returns the code implementing `FromFlags` for the struct, or an error message
+fn derive(input: TokenStream) -> Result<String, String> {
+    let mut tokens = input.into_iter();
+    let mut name = None;
+    while let Some(token) = tokens.next() {
+        if let TokenTree::Ident(ident) = &token {
+            match &ident.to_string()[..] {
+                "struct" => {
+                    name = tokens.next().map(|x| x.to_string());
+                    break;
+                }
+                "enum" | "union" => {
+                    return Err(String::from("Command can only be derived for structs"))
+                }
+                _ => {}
+            }
+        }
+    }
+    let name = name.ok_or("expected a struct")?;
+    let body = match tokens.next() {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => group.stream(),
+        Some(TokenTree::Punct(punct)) if punct.as_char() == '<' => {
+            return Err(String::from(
+                "Command can not be derived for generic structs",
+            ))
+        }
+        _ => {
+            return Err(String::from(
+                "Command can only be derived for structs with named fields",
+            ))
+        }
+    };
+    let fields = parse_fields(body)?;
+
+    let mut shorts: Vec<String> = RESERVED_SHORTS.iter().map(|x| String::from(*x)).collect();
+    let mut flags = String::new();
+    let mut values = String::new();
+    for field in fields.iter() {
+        let long = field.name.trim_start_matches("r#").replace('_', "-");
+        let short = long.chars().next().map(String::from).unwrap_or_default();
+        let short = if shorts.contains(&short) {
+            String::new()
+        } else {
+            shorts.push(short.clone());
+            short
+        };
+        let (constructor, value) = match &field.kind[..] {
+            "bool" => ("new_bool", "get_bool({}).unwrap_or(false)"),
+            "String" => (
+                "new_string",
+                "get_str({}).map(String::from).unwrap_or_default()",
+            ),
+            "Option<String>" => ("new_string", "get_str({}).map(String::from)"),
+            "i32" => ("new_int", "get_int({}).unwrap_or_default()"),
+            "Option<i32>" => ("new_int", "get_int({})"),
+            "f32" => ("new_float", "get_float({}).unwrap_or_default()"),
+            "Option<f32>" => ("new_float", "get_float({})"),
+            "Vec<String>" => (
+                "new_string_list_csv",
+                "get_list({}).map(|x| x.to_vec()).unwrap_or_default()",
+            ),
+            kind => return Err(format!("unsupported type {} of field {}", kind, field.name)),
+        };
+        flags.push_str(&format!(
+            "::cancer::flag::Flag::{}({:?}, {:?}, {:?}),",
+            constructor, short, long, field.description
+        ));
+        values.push_str(&format!(
+            "{}: ::cancer::flag::FlagSliceExt::{},",
+            field.name,
+            value.replace("{}", &format!("flags, {:?}", long))
+        ));
+    }
+
+    Ok(format!(
+        "impl ::cancer::flag::FromFlags for {name} {{
+            fn flags() -> ::std::vec::Vec<::cancer::flag::Flag> {{
+                ::std::vec![{flags}]
+            }}
+
+            fn from_flags(flags: &[&::cancer::flag::Flag]) -> Self {{
+                Self {{ {values} }}
+            }}
+        }}",
+        name = name,
+        flags = flags,
+        values = values
+    ))
+}
+
+/// Returns the fields of a struct body, split on commas outside of angle brackets
+fn parse_fields(body: TokenStream) -> Result<Vec<Field>, String> {
+    let mut fields = vec![];
+    let mut field: Vec<TokenTree> = vec![];
+    let mut depth = 0;
+    for token in body {
+        if let TokenTree::Punct(punct) = &token {
+            match punct.as_char() {
+                '<' => depth += 1,
+                '>' => depth -= 1,
+                ',' if depth == 0 => {
+                    fields.push(parse_field(std::mem::take(&mut field))?);
+                    continue;
+                }
+                _ => {}
+            }
+        }
+        field.push(token);
+    }
+    if !field.is_empty() {
+        fields.push(parse_field(field)?);
+    }
+
+    Ok(fields)
+}
+
+/// Returns a field from its tokens, like `/// doc` attributes, visibility, name, `:` and type
+fn parse_field(tokens: Vec<TokenTree>) -> Result<Field, String> {
+    let mut docs = vec![];
+    let mut tokens = tokens.into_iter().peekable();
+    let mut name = None;
+    while let Some(token) = tokens.next() {
+        match token {
+            TokenTree::Punct(punct) if punct.as_char() == '#' => {
+                if let Some(TokenTree::Group(group)) = tokens.next() {
+                    if let Some(doc) = doc_text(group.stream()) {
+                        docs.push(doc);
+                    }
+                }
+            }
+            TokenTree::Ident(ident) if ident.to_string() == "pub" => {
+                if matches!(tokens.peek(), Some(TokenTree::Group(x)) if x.delimiter() == Delimiter::Parenthesis)
+                {
+                    tokens.next();
+                }
+            }
+            TokenTree::Ident(ident) => {
+                name = Some(ident.to_string());
+                break;
+            }
+            _ => {
+                return Err(String::from(
+                    "Command can only be derived for structs with named fields",
+                ))
+            }
+        }
+    }
+    let name = name.ok_or("expected a field name")?;
+    match tokens.next() {
+        Some(TokenTree::Punct(punct)) if punct.as_char() == ':' => {}
+        _ => return Err(format!("expected a type of field {}", name)),
+    }
+    let kind: String = tokens
+        .map(|x| x.to_string())
+        .collect::<String>()
+        .replace(' ', "");
+    let description = docs
+        .iter()
+        .map(|x| x.trim())
+        .filter(|x| !x.is_empty())
+        .collect::<Vec<&str>>()
+        .join(" ");
+
+    Ok(Field {
+        name,
+        kind,
+        description,
+    })
+}
+
+/// Returns the text of a `doc = "..."` attribute, `None` for other attributes
+fn doc_text(attribute: TokenStream) -> Option<String> {
+    let tokens: Vec<TokenTree> = attribute.into_iter().collect();
+    match &tokens[..] {
+        [TokenTree::Ident(ident), TokenTree::Punct(punct), TokenTree::Literal(literal)]
+            if ident.to_string() == "doc" && punct.as_char() == '=' =>
+        {
+            Some(unquote(&literal.to_string()))
+        }
+        _ => None,
+    }
+}
+
+/// Returns the content of a string literal, e.g. `say "hi"` for `"say \"hi\""`
+fn unquote(literal: &str) -> String {
+    if let Some(raw) = literal.strip_prefix('r') {
+        let hashes = raw.len() - raw.trim_start_matches('#').len();
+        return String::from(&raw[hashes + 1..raw.len() - hashes - 1]);
+    }
+
+    let mut text = String::new();
+    let mut chars = literal[1..literal.len() - 1].chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => text.push('\n'),
+            Some('t') => text.push('\t'),
+            Some('r') => text.push('\r'),
+            Some('0') => text.push('\0'),
+            Some(c) => text.push(c),
+            None => {}
+        }
+    }
+
+    text
+}
@@ -0,0 +1,55 @@
+use cancer::{command::Command, flag::FromFlags};
+
+#[derive(cancer::Command, Debug, PartialEq)]
+struct Options {
+    /// times to say hello
+    count: i32,
+    /// name to greet,
+    /// "world" by default
+    name: Option<String>,
+    verbose: bool,
+    tags: Vec<String>,
+}
+
+fn args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|x| String::from(*x)).collect()
+}
+
+#[test]
+fn derive_flags() {
+    let flags = Options::flags();
+    let names: Vec<String> = flags.iter().map(|x| x.names("-", "--")).collect();
+    assert_eq!(
+        names,
+        vec!["-c, --count", "-n, --name", "--verbose", "-t, --tags"]
+    );
+    assert_eq!(flags[0].description, "times to say hello");
+    assert_eq!(flags[1].description, "name to greet, \"world\" by default");
+}
+
+#[test]
+fn derive_from_flags() {
+    let mut command = Command::new("test description", "test", |_, _, _| Ok(()));
+    command.add_flags_from::<Options>();
+    let (_, flags) = command
+        .parse_args_with(args(&[
+            "test",
+            "--count",
+            "3",
+            "-n",
+            "world",
+            "--verbose",
+            "text",
+        ]))
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        Options::from_flags(&flags),
+        Options {
+            count: 3,
+            name: Some(String::from("world")),
+            verbose: true,
+            tags: vec![],
+        }
+    );
+}
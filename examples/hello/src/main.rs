@@ -1,37 +1,24 @@
-use std::io::{stdout, BufWriter};
-
-use cancer::{
-    command::Command,
-    flag::{Flag, FlagValue},
-};
+use cancer::{command::Command, flag::Flag};
 use ferris_says::say;
 
 fn main() {
-    let mut command = Command::new("gives a friendly hello", "hello TEXT", |text, flags| {
-        let mut use_ferris = false;
+    let mut command = Command::new(
+        "gives a friendly hello",
+        "hello TEXT",
+        |args, flags, out| {
+            let use_ferris = flags.get_bool("ferris").unwrap_or(false);
 
-        for flag in flags {
-            if flag.is_match("-f") {
-                match flag.value {
-                    FlagValue::Bool(value) => {
-                        use_ferris = value;
-                    }
-                    _ => (),
-                }
+            let message = format!("hello, {}!", args[0]);
+            if use_ferris {
+                let width = message.chars().count();
+                say(message.as_str(), width, out)?;
+            } else {
+                writeln!(out, "{}", message)?;
             }
-        }
-
-        let message = format!("hello, {}!", text.unwrap());
-        if use_ferris {
-            let stdout = stdout();
-            let width = message.chars().count();
 
-            let mut writer = BufWriter::new(stdout.lock());
-            say(message.as_str(), width, &mut writer).unwrap();
-        } else {
-            println!("{}", message);
-        }
-    });
+            Ok(())
+        },
+    );
     command.add_flag(Flag::new_bool("f", "ferris", "say hello from ferris"));
     command.execute();
 }
@@ -1,50 +1,327 @@
+use std::{
+    env,
+    error::Error,
+    fs,
+    io::{self, Cursor, IsTerminal, Read, Write},
+    ops::Deref,
+    path::{Path, PathBuf},
+    process::{self, Command as Process, Stdio},
+};
+#[cfg(feature = "async")]
+use std::{future::Future, pin::Pin};
+
 use crate::{
-    help::{DefaultHelpRender, HelpRender},
-    util::get_args,
+    completion::{completion_script, Shell},
+    config::{ConfigLoader, ConfigValue, JsonConfigLoader},
+    error::CommandError,
+    help::{CompactHelpRender, DefaultHelpRender, HelpRender, TemplateHelpRender},
+    theme::Theme,
+    util::{
+        get_args, json_string, kebab_case, levenshtein, paint, parse_bool, program_name,
+        strip_accents, use_color,
+    },
     version::{DefaultVersionRender, VersionRender},
-    PKG_NAME,
+    PKG_NAME, PKG_VERSION,
 };
 
-use super::flag::{is_flag, Flag, FlagValue};
+use super::flag::{
+    is_flag_with, Flag, FlagSet, FlagValue, FromFlags, ValueSource, FLAG_LONG_START,
+    FLAG_SHORT_START,
+};
 
 const HELP_SHORT: &str = "h";
 const HELP_LONG: &str = "help";
+const HELP_ALL_LONG: &str = "help-all";
 const VERSION_SHORT: &str = "v";
 const VERSION_LONG: &str = "version";
+const ERROR_EXIT_CODE: i32 = 1;
+const SUGGESTION_DISTANCE: usize = 2;
+const DEFAULT_PAGER: &str = "less -R";
+const COMPLETION_COMMAND: &str = "completion";
+
+/// A constraint on the count of positional args
+enum Arity {
+    /// Exactly the count
+    Exact(usize),
+    /// At least the count
+    Min(usize),
+    /// Between the lower and upper counts, inclusive
+    Range(usize, usize),
+}
+
+/// Positional args passed to the logic of a command, dereferences to positional values
+///
+/// # Example
+///
+/// ```
+/// # use cancer::command::Command;
+/// let command = Command::new("runs a command", "exec -- COMMAND", |args, _, _| {
+///     println!("{} positionals, running {:?}", args.len(), args.trailing);
+///     Ok(())
+/// });
+/// ```
+#[derive(Debug, Default, PartialEq)]
+pub struct Args {
+    /// Positional args without the program name and flags, converted to declared types
+    pub values: Vec<FlagValue>,
+    /// Args after the `--` separator delivered verbatim, empty when a trailing flag captures them
+    pub trailing: Vec<String>,
+    /// Positional args like `key=value` split into pairs when `kv_positionals` is set, in the order given
+    pub pairs: Vec<(String, String)>,
+}
+
+impl Deref for Args {
+    type Target = [FlagValue];
+
+    fn deref(&self) -> &Self::Target {
+        &self.values
+    }
+}
+
+/// A function type that holds the logic of a command
+///
+/// # Arguments
+///
+/// `args` - An `Args` that holds positional args converted to declared types and args after `--`
+/// `flags` - A `FlagSet` that holds added flags without help or version
+/// `out` - The writer of the command, stdout by default, for streaming output without building a whole string
+///
+/// # Return
+///
+/// `Ok(())` on success, or an error which makes the command exit with a nonzero code
+pub type Run = fn(args: Args, flags: &FlagSet, out: &mut dyn Write) -> Result<(), Box<dyn Error>>;
+
+/// An async function which holds the logic of a command, available with the `async` feature
+///
+/// # Arguments
+///
+/// `args` - An `Args` that holds positional args converted to declared types and args after `--`
+///
+/// `flags` - A `FlagSet` of parsed flags
+///
+/// `out` - The writer which output is written to, flushed after the returned future completes
+///
+/// # Return
+///
+/// A future which resolves to `Ok(())` on success, or an error which makes the command exit with a nonzero code
+#[cfg(feature = "async")]
+pub type AsyncRun = for<'a> fn(
+    args: Args,
+    flags: FlagSet<'a>,
+    out: &'a mut dyn Write,
+) -> Pin<Box<dyn Future<Output = Result<(), Box<dyn Error>>> + 'a>>;
+
+/// A hook which is called with parsed flags before the logic of a command
+///
+/// # Arguments
+///
+/// `flags` - A `FlagSet` that holds the flags passed to `run`, inherited global flags included and help or version excluded
+pub type ParsedHook = Box<dyn Fn(&FlagSet)>;
+
+/// A formatter which renders an error into the text written to the error writer
+///
+/// # Arguments
+///
+/// `err` - The error of a failed execution
+pub type ErrorRender = Box<dyn Fn(&CommandError) -> String>;
+
+/// A handler which is called instead of an error when the first positional matches no subcommand, e.g. to run `hello-foo` for `hello foo`
+///
+/// # Arguments
+///
+/// `name` - The first positional, which is the name of the external subcommand
+///
+/// `args` - Args after the name
+pub type ExternalSubcommand = Box<dyn Fn(&str, &[String])>;
+
+/// An arg parsed by a command
+enum ParsedArg {
+    /// An arg which is not a flag
+    Simple(String),
+    /// An arg which matches a flag, holds the arg, the index of the flag and its resolved value
+    Flag(String, usize, FlagValue),
+    /// Args after the `--` separator when no trailing flag captures them
+    Trailing(Vec<String>),
+    /// An arg which looks like a flag but does not match any flag
+    Unknown(String),
+    /// An arg which matches a flag but can not be resolved, holds the arg and the error message
+    Invalid(String, String),
+}
 
 /// A command type
 pub struct Command {
+    /// Command name which is the basename of the program by default, used in help, version and errors
+    ///
+    /// # Example
+    ///
+    /// hello
+    pub name: String,
+    /// Whether the name is set explicitly rather than from the program
+    name_explicit: bool,
+    /// Version of the program which overrides the crate version in version information
+    version: Option<String>,
+    /// Whether the command is added as a subcommand, whose usage is prefixed with its full path in help
+    is_subcommand: bool,
     /// Command description
     ///
     /// # Example
     ///
     /// gives a friendly hello
     pub description: String,
+    /// Command long description which is printed in its own help instead of the description, which stays
+    /// a one-line summary in subcommand listings
+    ///
+    /// # Example
+    ///
+    /// gives a friendly hello to the given name, or to ferris with the ferris flag
+    pub long_description: Option<String>,
     /// Command usage
     ///
     /// # Example
     ///
     /// hello TEXt
     pub usage: String,
+    /// Command preamble which is printed before the description in help
+    ///
+    /// # Example
+    ///
+    /// Copyright (c) hello authors
+    pub before_help: Option<String>,
+    /// Command epilogue which is printed after the flags section in help
+    ///
+    /// # Example
+    ///
+    /// see also: hello-world
+    pub after_help: Option<String>,
+    /// Prefix of flag short identifier, `-` by default
+    pub flag_short_start: String,
+    /// Prefix of flag long identifier, `--` by default, which also separates args when used alone
+    pub flag_long_start: String,
+    /// Whether the flag prefixes are set explicitly rather than inherited from the parent command
+    flag_prefixes_explicit: bool,
     /// Command flags
     pub flags: Vec<Flag>,
+    /// Global flags inherited from the parent command, parsed along with command flags
+    pub global_flags: Vec<Flag>,
+    /// Subcommands with their names, dispatched to by the first positional
+    subcommands: Vec<(String, Command)>,
+    /// Alternate names which the command is dispatched to by as a subcommand, e.g. `co` for `checkout`
+    aliases: Vec<String>,
+    /// Whether the command is omitted from help and completion of its parent command as a subcommand
+    hidden: bool,
+    /// Name of the subcommand to dispatch to when the first positional matches no subcommand
+    default_subcommand: Option<String>,
+    /// Handler of the first positional and args after it when it matches no subcommand and no default subcommand is set
+    external_subcommand: Option<ExternalSubcommand>,
+    /// Prefix of environment variables which flags fall back to when not provided in args
+    env_prefix: Option<String>,
+    /// Loader of a config file which flags fall back to when not provided in args or environment variables
+    config_loader: Option<Box<dyn ConfigLoader>>,
+    /// Loaders of JSON config files in the order added, values of later files override earlier ones
+    config_files: Vec<JsonConfigLoader>,
+    /// Whether `@path` args are expanded to the tokens of the file
+    response_files: bool,
+    /// Whether camelCase long flags are converted to kebab-case before matching
+    normalize_flags: bool,
+    /// Whether accents are stripped from long flags before matching
+    normalize_unicode: bool,
+    /// Whether a `-` positional arg is replaced by the text read from the input
+    dash_stdin: bool,
+    /// Whether positional args like `key=value` are split into pairs instead of kept as positional args
+    kv_positionals: bool,
+    /// Constraint on the count of positional args, help is printed when no positional is given without a constraint
+    arity: Option<Arity>,
+    /// Whether any count of positional args is taken, the last declared positional repeats
+    variadic: bool,
+    /// Names and types of declared positional args in order, undeclared positional args are strings
+    positionals: Vec<(String, FlagValue)>,
+    /// Extra version lines like dependency versions, rendered with verbose version
+    version_lines: Vec<String>,
+    /// Usage examples with their descriptions, rendered in help
+    examples: Vec<(String, String)>,
     /// Command execution logic
     ///
     /// # Arguments
     ///
-    /// `text` - An optional string that holds input text
-    /// `flags` - A vector of Flag that holds added flags without help or version
+    /// `args` - An `Args` that holds positional args converted to declared types and args after `--`
+    /// `flags` - A `FlagSet` that holds added flags without help or version
     ///
     /// # Example
     ///
+    /// ```ignore
+    /// |args, flags| {
+    ///     println!("hello, {}!", args[0]);
+    ///     Ok(())
+    /// }
     /// ```
-    /// |text, flags| { println!("hello, {}!", text.unwrap()); }
-    /// ```
-    run: fn(text: Option<String>, flags: Vec<&Flag>),
-    /// Help render which is a `HelpRender` trait that supports for rendering help information
-    help_render: Box<dyn HelpRender>,
-    /// Version render which is a `VersionRender` trait that supports for rendering version information
-    version_render: Box<dyn VersionRender>,
+    run: Run,
+    /// Async logic of the command awaited by `execute_async`, set by `new_async`
+    #[cfg(feature = "async")]
+    run_async: Option<AsyncRun>,
+    /// Help render which is a `HelpRender` trait that supports for rendering help information, `None` for no help
+    help_render: Option<Box<dyn HelpRender>>,
+    /// Compact help render which is used when help is requested by the short help flag, `None` for full help
+    compact_help_render: Option<Box<dyn HelpRender>>,
+    /// Whether help was requested by the short help flag in the latest parsed args
+    help_short: bool,
+    /// Whether help is being rendered for the help all flag, which includes hidden flags
+    help_all: bool,
+    /// Args after the `--` separator in the latest parsed args when no trailing flag captures them
+    trailing: Vec<String>,
+    /// Version render which is a `VersionRender` trait that supports for rendering version information, `None` for no version
+    version_render: Option<Box<dyn VersionRender>>,
+    /// Writer which help and version information is written to, `stdout` by default
+    writer: Box<dyn Write>,
+    /// Whether the writer is `stdout`, help is only paged to a terminal `stdout`
+    writer_is_stdout: bool,
+    /// Reader of a `-` positional arg, stdin by default
+    input: Box<dyn Read>,
+    /// Whether the input is `stdin`, which is only read as a fallback when it is not a terminal
+    input_is_stdin: bool,
+    /// Returns if stdin is a terminal, replaceable in tests
+    stdin_is_terminal: fn() -> bool,
+    /// Whether the positional is read from the input when no positional is given and the input is piped
+    stdin_fallback: bool,
+    /// Whether flags are listed alphabetically in help
+    sort_flags: bool,
+    /// Whether parse decisions are written to the error writer
+    trace: bool,
+    /// Whether missing required flags are prompted for when stdin is a terminal
+    interactive: bool,
+    /// Groups of short or long identifiers, at least one flag of each group must be set
+    required_groups: Vec<Vec<String>>,
+    /// Hooks called with parsed flags before `run`, in order of registration
+    parsed_hooks: Vec<ParsedHook>,
+    /// Whether the hidden `completion <shell>` subcommand is enabled
+    completion_command: bool,
+    /// Whether help is piped into a pager
+    paged_help: bool,
+    /// Locale of help text, e.g. `de`
+    locale: Option<String>,
+    /// Writer which errors are written to, `stderr` by default
+    error_writer: Box<dyn Write>,
+    /// Whether errors are colored red, detected by whether the error writer is a terminal `stderr` when not set
+    error_color: Option<bool>,
+    /// Whether the error writer is `stderr`
+    error_writer_is_stderr: bool,
+    /// Colors of help and errors
+    theme: Theme,
+    /// Formatter of errors, errors are prefixed with the command name when not set
+    error_render: Option<ErrorRender>,
+    /// Whether the usage footer of parse errors ends with a hint of the help flag
+    help_hint: bool,
+    /// Whether parse errors are followed by the short usage footer
+    print_usage_on_error: bool,
+    /// Whether parse errors are followed by the full help instead of the short usage footer
+    help_on_error: bool,
+    /// Exit code of `execute` after help or version is printed
+    help_exit_code: i32,
+    /// Exit code of `execute` when the command fails
+    error_exit_code: i32,
+    /// Whether help or version was printed by the last execution, by the command or a subcommand
+    help_shown: bool,
+    /// Exits the process with a code, replaceable in tests
+    exit: fn(i32),
 }
 
 impl Command {
@@ -54,37 +331,301 @@ impl Command {
     ///
     /// * `description` - A string slice that holds the description of the command
     /// * `usage` - A string slice that holds the usage of the command
-    /// * `run` - A function with an optional string parameter that holds the logic of the command, this function will be called on command execution
+    /// * `run` - A function with positional args and flags parameters that holds the logic of the command, this function will be called on command execution and returns an error on failure
     ///
     /// # Examples
     ///
     /// ```
-    /// let mut command = Command::new("gives a friendly hello", "hello TEXT", |text, flags| {
-    ///     println!("hello, {}!", text.unwrap());
+    /// # use cancer::command::Command;
+    /// let mut command = Command::new("gives a friendly hello", "hello TEXT", |args, flags, _| {
+    ///     println!("hello, {}!", args[0]);
+    ///     Ok(())
     /// });
     /// ```
-    pub fn new(description: &str, usage: &str, run: fn(Option<String>, Vec<&Flag>)) -> Self {
+    pub fn new(description: &str, usage: &str, run: Run) -> Self {
         let mut command = Self {
+            name: String::from(PKG_NAME),
+            name_explicit: false,
+            version: None,
+            is_subcommand: false,
             description: String::from(description),
+            long_description: None,
             usage: String::from(usage),
+            before_help: None,
+            after_help: None,
             run,
+            #[cfg(feature = "async")]
+            run_async: None,
+            flag_short_start: String::from(FLAG_SHORT_START),
+            flag_long_start: String::from(FLAG_LONG_START),
+            flag_prefixes_explicit: false,
             flags: vec![],
-            help_render: Box::new(DefaultHelpRender::new()),
-            version_render: Box::new(DefaultVersionRender::new()),
+            global_flags: vec![],
+            subcommands: vec![],
+            aliases: vec![],
+            hidden: false,
+            default_subcommand: None,
+            external_subcommand: None,
+            env_prefix: None,
+            config_loader: None,
+            config_files: vec![],
+            response_files: false,
+            normalize_flags: false,
+            normalize_unicode: false,
+            dash_stdin: false,
+            kv_positionals: false,
+            arity: None,
+            variadic: false,
+            positionals: vec![],
+            version_lines: vec![],
+            examples: vec![],
+            help_render: Some(Box::new(DefaultHelpRender::new())),
+            compact_help_render: Some(Box::new(CompactHelpRender::new())),
+            help_short: false,
+            help_all: false,
+            trailing: vec![],
+            version_render: Some(Box::new(DefaultVersionRender::new())),
+            writer: Box::new(io::stdout()),
+            writer_is_stdout: true,
+            input: Box::new(io::stdin()),
+            input_is_stdin: true,
+            stdin_is_terminal: || io::stdin().is_terminal(),
+            stdin_fallback: false,
+            sort_flags: false,
+            trace: false,
+            interactive: false,
+            required_groups: vec![],
+            parsed_hooks: vec![],
+            completion_command: false,
+            paged_help: false,
+            locale: None,
+            error_writer: Box::new(io::stderr()),
+            error_color: None,
+            error_writer_is_stderr: true,
+            theme: Theme::default(),
+            error_render: None,
+            help_hint: true,
+            print_usage_on_error: true,
+            help_on_error: false,
+            help_exit_code: 0,
+            error_exit_code: ERROR_EXIT_CODE,
+            help_shown: false,
+            exit: |code| process::exit(code),
         };
-        command.add_flag(Flag::new_bool(
-            HELP_SHORT,
-            HELP_LONG,
-            &format!("help for {}", PKG_NAME),
-        ));
-        command.add_flag(Flag::new_bool(
-            VERSION_SHORT,
-            VERSION_LONG,
-            &format!("version for {}", PKG_NAME),
-        ));
+        command.add_flag(Flag::new_bool(HELP_SHORT, HELP_LONG, "").reserved());
+        command.add_flag(Flag::new_bool(VERSION_SHORT, VERSION_LONG, "").reserved());
+        command.set_name(PKG_NAME);
+
+        command
+    }
+
+    /// Returns a command with the arguments provided whose logic is async, available with the `async` feature
+    ///
+    /// The command is executed by `execute_async`, executing it synchronously fails with an error
+    ///
+    /// # Arguments
+    ///
+    /// * `description` - A string slice that holds the description of the command
+    /// * `usage` - A string slice that holds the usage of the command
+    /// * `run` - An async function with positional args and flags parameters that holds the logic of the command
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::io::Write;
+    /// # use cancer::command::Command;
+    /// let mut command = Command::new_async("gives a friendly hello", "hello TEXT", |args, _, out| {
+    ///     Box::pin(async move {
+    ///         writeln!(out, "hello, {}!", args[0])?;
+    ///         Ok(())
+    ///     })
+    /// });
+    /// let future = command.execute_async(vec![String::from("hello"), String::from("world")]);
+    /// ```
+    #[cfg(feature = "async")]
+    pub fn new_async(description: &str, usage: &str, run: AsyncRun) -> Self {
+        let mut command = Command::new(description, usage, |_, _, _| {
+            Err("async command must be executed by execute_async".into())
+        });
+        command.run_async = Some(run);
+
+        command
+    }
+
+    /// Returns a command with the arguments provided but without help and version flags or renders,
+    /// for full control over every flag and output
+    ///
+    /// Flags added by the user like `-h` or `-v` are ordinary flags passed to `run`,
+    /// help is not printed when no arg is provided unless a help render is set
+    ///
+    /// # Arguments
+    ///
+    /// * `description` - A string slice that holds the description of the command
+    /// * `usage` - A string slice that holds the usage of the command
+    /// * `run` - A function with positional args and flags parameters that holds the logic of the command
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// let command = Command::minimal("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
+    ///
+    /// assert_eq!(0, command.flag_count(true));
+    /// ```
+    pub fn minimal(description: &str, usage: &str, run: Run) -> Self {
+        let mut command = Command::new(description, usage, run);
+        command.flags.clear();
+        command.help_render = None;
+        command.compact_help_render = None;
+        command.version_render = None;
+        command.help_hint = false;
 
         command
     }
+
+    /// Returns the command with a name used instead of the basename of the program
+    ///
+    /// # Arguments
+    ///
+    /// `name` - A string slice that holds the command name
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// let command = Command::new("gives a friendly hello", "hello TEXT", |args, flags, _| {
+    ///     println!("hello, {}!", args[0]);
+    ///     Ok(())
+    /// })
+    /// .name("hello");
+    /// ```
+    pub fn name(mut self, name: &str) -> Self {
+        self.set_name(name);
+        self.name_explicit = true;
+        self
+    }
+
+    /// Returns the command with a description replacing the one given to `new`
+    ///
+    /// # Arguments
+    ///
+    /// `text` - A string slice that holds the description
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// let command = Command::new("", "hello TEXT", |_, _, _| Ok(())).description("gives a friendly hello");
+    /// ```
+    pub fn description(mut self, text: &str) -> Self {
+        self.description = String::from(text);
+        self
+    }
+
+    /// Returns the command with a usage replacing the one given to `new`
+    ///
+    /// # Arguments
+    ///
+    /// `text` - A string slice that holds the usage
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// let command = Command::new("gives a friendly hello", "", |_, _, _| Ok(())).usage("hello TEXT");
+    /// ```
+    pub fn usage(mut self, text: &str) -> Self {
+        self.usage = String::from(text);
+        self
+    }
+
+    /// Returns the command with a long description which is printed in its own help instead of the description
+    ///
+    /// # Arguments
+    ///
+    /// `text` - A string slice that holds the long description
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// let command = Command::new("gives a friendly hello", "hello TEXT", |args, flags, _| {
+    ///     println!("hello, {}!", args[0]);
+    ///     Ok(())
+    /// })
+    /// .long_description("gives a friendly hello to the given name, or to ferris with the ferris flag");
+    /// ```
+    pub fn long_description(mut self, text: &str) -> Self {
+        self.long_description = Some(String::from(text));
+        self
+    }
+
+    /// Returns the command with a preamble which is printed before the description in help
+    ///
+    /// # Arguments
+    ///
+    /// `text` - A string slice that holds the preamble text, e.g. a banner or copyright line
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// let command = Command::new("gives a friendly hello", "hello TEXT", |args, flags, _| {
+    ///     println!("hello, {}!", args[0]);
+    ///     Ok(())
+    /// })
+    /// .before_help("Copyright (c) hello authors");
+    /// ```
+    pub fn before_help(mut self, text: &str) -> Self {
+        self.before_help = Some(String::from(text));
+        self
+    }
+
+    /// Returns the command with an epilogue which is printed after the flags section in help
+    ///
+    /// # Arguments
+    ///
+    /// `text` - A string slice that holds the epilogue text
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// let command = Command::new("gives a friendly hello", "hello TEXT", |args, flags, _| {
+    ///     println!("hello, {}!", args[0]);
+    ///     Ok(())
+    /// })
+    /// .after_help("see also: hello-world");
+    /// ```
+    pub fn after_help(mut self, text: &str) -> Self {
+        self.after_help = Some(String::from(text));
+        self
+    }
+
+    /// Returns the command with the version of the program, which version renders prefer over the crate version
+    ///
+    /// # Arguments
+    ///
+    /// `ver` - A string slice that holds the version, e.g. `env!("CARGO_PKG_VERSION")` of the program
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// let command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()))
+    ///     .version_string_value("2.1.0");
+    ///
+    /// assert_eq!("2.1.0", command.get_version());
+    /// ```
+    pub fn version_string_value(mut self, ver: &str) -> Self {
+        self.version = Some(String::from(ver));
+        self
+    }
+
+    /// Returns the version of the program set by `version_string_value`, or the crate version
+    pub fn get_version(&self) -> &str {
+        self.version.as_deref().unwrap_or(PKG_VERSION)
+    }
 }
 
 impl Command {
@@ -97,163 +638,5503 @@ impl Command {
     /// # Examples
     ///
     /// ```
+    /// # use cancer::{command::Command, flag::Flag};
+    /// # let mut command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
     /// command.add_flag(Flag::new_bool("f", "ferris", "say hello from ferris"));
     /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `flag` is a trailing flag and the command already holds one
     pub fn add_flag(&mut self, flag: Flag) {
+        assert!(
+            !(flag.trailing && self.flags.iter().any(|x| x.trailing)),
+            "only one trailing flag is allowed per command"
+        );
         self.flags.push(flag);
     }
-}
 
-impl Command {
-    /// Execute command
+    /// Add the flags which a `FromFlags` type is built from, so `T::from_flags(flags)` builds it in `run`
     ///
-    /// # Example
+    /// # Examples
     ///
     /// ```
-    /// let mut command = Command::new("gives a friendly hello", "hello TEXT", |text, flags| {
-    ///     todo!();
+    /// # use cancer::{command::Command, flag::{Flag, FlagSliceExt, FromFlags}};
+    /// struct Options {
+    ///     count: i32,
+    /// }
+    ///
+    /// impl FromFlags for Options {
+    ///     fn flags() -> Vec<Flag> {
+    ///         vec![Flag::new_int("c", "count", "times to say hello")]
+    ///     }
+    ///
+    ///     fn from_flags(flags: &[&Flag]) -> Self {
+    ///         Self {
+    ///             count: flags.get_int("count").unwrap_or(1),
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let mut command = Command::new("gives a friendly hello", "hello TEXT", |args, flags, _| {
+    ///     for _ in 0..Options::from_flags(flags).count {
+    ///         println!("hello, {}!", args[0]);
+    ///     }
+    ///     Ok(())
     /// });
-    /// command.execute();
+    /// command.add_flags_from::<Options>();
     /// ```
-    pub fn execute(&mut self) {
-        let args = get_args();
-
-        let args = self.update_flags(args.iter().map(|x| &x[..]).collect());
-
-        if self.help_exit() {
-            return;
-        }
-        if self.version_exit() {
-            return;
-        }
-
-        if args.len() <= 1 {
-            println!("{}", self.help_render.help_text(self));
-            return;
+    pub fn add_flags_from<T: FromFlags>(&mut self) {
+        for flag in T::flags() {
+            self.add_flag(flag);
         }
-
-        let input = &args[1];
-        let flags = self.get_flags();
-        (self.run)(Some(String::from(input)), flags);
     }
 
-    /// Update flags value by giving args and returns simple args vector without any flag
+    /// Returns a mutable reference to an added flag by its short or long identifier
+    ///
+    /// Values are reset to defaults before each execution, set `default` for a value that takes effect in executions
     ///
     /// # Arguments
     ///
-    /// `args` - A vector of string slice that holds arguments
+    /// `name` - A string slice that holds the short or long identifier without prefix
     ///
-    /// # Return
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::{command::Command, flag::{Flag, FlagValue}};
+    /// # let mut command = Command::new("serves greetings", "hello", |_, _, _| Ok(()));
+    /// command.add_flag(Flag::new_int("p", "port", "port to listen on"));
+    /// command.flag_mut("port").unwrap().default = FlagValue::Int(Some(9090));
+    /// ```
+    pub fn flag_mut(&mut self, name: &str) -> Option<&mut Flag> {
+        self.flags.iter_mut().find(|x| x.is_named(name))
+    }
+
+    /// Returns the count of flags, including global flags inherited from parent commands
     ///
-    /// A vector of string that without any flag from args
+    /// # Arguments
     ///
-    /// # Example
+    /// `include_reserved` - Whether help and version flags are counted
+    ///
+    /// # Examples
     ///
     /// ```
-    /// let args = vec!["target/debug/hello", "-f", "world"];
-    /// let args = self.set_flags(args);
-    /// dbg!(&args);
-    /// // output:
-    /// // [src/command.rs:95] &args = [
-    /// //     "target/debug/hello",
-    /// //     "world",
-    /// // ]
+    /// # use cancer::command::Command;
+    /// let command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
+    ///
+    /// assert_eq!(0, command.flag_count(false));
+    /// assert_eq!(2, command.flag_count(true));
     /// ```
-    fn update_flags(&mut self, args: Vec<&str>) -> Vec<String> {
-        let mut simple_args: Vec<String> = vec![];
-
-        for arg in args {
-            if !(is_flag(arg)) {
-                simple_args.push(String::from(arg));
-                continue;
-            }
-            for mut flag in self.flags.iter_mut() {
-                if flag.is_match(arg) {
-                    flag.value = FlagValue::Bool(true);
-                }
-            }
+    pub fn flag_count(&self, include_reserved: bool) -> usize {
+        if include_reserved {
+            self.all_flags().count()
+        } else {
+            self.get_flags().len()
         }
+    }
 
-        simple_args
+    /// Returns if a flag is added by its short or long identifier, including global flags inherited from parent commands
+    ///
+    /// # Arguments
+    ///
+    /// `name` - A string slice that holds the short or long identifier without prefix
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// let command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
+    ///
+    /// assert!(command.has_flag("help"));
+    /// assert!(!command.has_flag("ferris"));
+    /// ```
+    pub fn has_flag(&self, name: &str) -> bool {
+        self.all_flags().any(|x| x.is_named(name))
     }
 
-    /// Returns added flags without help or version
+    /// Returns the current value of a flag by its short or long identifier, including global flags inherited from
+    /// parent commands, which holds the parsed value after execution
     ///
-    /// # Example
+    /// # Arguments
+    ///
+    /// `name` - A string slice that holds the short or long identifier without prefix
+    ///
+    /// # Examples
     ///
     /// ```
-    /// // returns empty flag vector when not providing any flag
-    /// let mut command = Command::new("gives a friendly hello", "hello TEXT", |text, flags| {
-    ///     todo!();
-    /// });
-    /// let flags = command.get_flags();
-    /// assert_eq!(0, flags.len());
+    /// # use cancer::{command::Command, flag::{Flag, FlagValue}};
+    /// let mut command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
+    /// command.add_flag(Flag::new_int("c", "count", "count of greetings"));
+    /// let args = ["hello", "--count", "3", "world"].map(String::from).to_vec();
+    /// command.try_execute(args).unwrap();
     ///
-    /// // returns non empty flag vector when providing flag
-    /// let mut command = Command::new("gives a friendly hello", "hello TEXT", |text, flags| {
-    ///     todo!();
-    /// });
-    /// command.add_flag("f", "ferris", "say hello from ferris");
-    /// let flags = command.get_flags();
-    /// assert_eq!(1, flags.len());
+    /// assert_eq!(Some(&FlagValue::Int(Some(3))), command.flag_value("count"));
     /// ```
-    fn get_flags(&self) -> Vec<&Flag> {
-        let mut simple_flags: Vec<&Flag> = vec![];
+    pub fn flag_value(&self, name: &str) -> Option<&FlagValue> {
+        self.all_flags()
+            .find(|x| x.is_named(name))
+            .map(|x| &x.value)
+    }
 
-        for flag in self.flags.iter() {
-            if !(flag.short == HELP_SHORT || flag.short == VERSION_SHORT) {
-                simple_flags.push(flag);
-            }
-        }
+    /// Returns the definitions of flags and global flags inherited from parent commands as a JSON array,
+    /// without help or version flags, for external tooling
+    ///
+    /// Each flag is an object of `short`, `long`, `description`, `type`, `default` and `required`,
+    /// where an unset default is `null`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::{command::Command, flag::Flag};
+    /// let mut command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
+    /// command.add_flag(Flag::new_int("c", "count", "count of greetings").required());
+    ///
+    /// assert_eq!(
+    ///     r#"[{"short":"c","long":"count","description":"count of greetings","type":"integer","default":null,"required":true}]"#,
+    ///     command.flags_json()
+    /// );
+    /// ```
+    pub fn flags_json(&self) -> String {
+        let flags: Vec<String> = self
+            .all_flags()
+            .filter(|x| !is_reserved(x))
+            .map(|x| {
+                format!(
+                    r#"{{"short":{},"long":{},"description":{},"type":{},"default":{},"required":{}}}"#,
+                    json_string(&x.short),
+                    json_string(&x.long),
+                    json_string(&x.description),
+                    json_string(type_name(&x.default)),
+                    json_value(&x.default),
+                    x.required
+                )
+            })
+            .collect();
 
-        simple_flags
+        format!("[{}]", flags.join(","))
     }
 
-    /// Check if help needed to display and exit,
+    /// Add a new subcommand to command, which is dispatched to when the first positional matches its name
     ///
-    /// by if arguments contains `-h` or `--help`
-    fn help_exit(&self) -> bool {
-        let mut exit = false;
-
-        for flag in self.flags.iter() {
-            if flag.short == HELP_SHORT {
-                match flag.value {
-                    FlagValue::Bool(value) => {
-                        if value {
-                            println!("{}", self.help_render.help_text(self));
-                            exit = true;
-                            break;
-                        }
-                    }
-                    _ => (),
-                }
-            }
+    /// Args before the subcommand name are parsed by the command, the name and args after it are passed to the subcommand,
+    /// the usage of the subcommand is prefixed with its full path like `hello say` in help
+    ///
+    /// # Arguments
+    ///
+    /// `name` - A string slice that holds the subcommand name
+    ///
+    /// `command` - A `Command` object
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// # let mut command = Command::new("manages greetings", "hello COMMAND", |_, _, _| Ok(()));
+    /// command.add_subcommand(
+    ///     "say",
+    ///     Command::new("gives a friendly hello", "TEXT", |args, flags, _| {
+    ///         println!("hello, {}!", args[0]);
+    ///         Ok(())
+    ///     }),
+    /// );
+    /// ```
+    pub fn add_subcommand(&mut self, name: &str, mut command: Command) {
+        command.is_subcommand = true;
+        if !command.name_explicit {
+            command.set_name(&format!("{} {}", self.name, name));
         }
-
-        exit
+        if !command.flag_prefixes_explicit {
+            command.set_flag_prefixes(&self.flag_short_start, &self.flag_long_start);
+        }
+        self.subcommands.push((String::from(name), command));
     }
 
-    /// Check if version needed to display and exit,
+    /// Add an alternate name which the command is dispatched to by as a subcommand, e.g. `co` for `checkout`
+    ///
+    /// Aliases are listed in parentheses after the subcommand name in help, `validate` checks they collide with
+    /// no other subcommand name or alias
+    ///
+    /// # Arguments
+    ///
+    /// `alias` - A string slice that holds the alternate name
     ///
-    /// by if arguments contains `-v` or `--version`
-    fn version_exit(&self) -> bool {
-        let mut exit = false;
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// let mut checkout = Command::new("switches branches", "BRANCH", |_, _, _| Ok(()));
+    /// checkout.add_alias("co");
+    /// ```
+    pub fn add_alias(&mut self, alias: &str) {
+        self.aliases.push(String::from(alias));
+    }
 
-        for flag in self.flags.iter() {
-            if flag.short == VERSION_SHORT {
-                match flag.value {
-                    FlagValue::Bool(value) => {
-                        if value {
-                            println!("{}", self.version_render.version_text(self));
-                            exit = true;
-                            break;
-                        }
-                    }
-                    _ => (),
-                }
+    /// Returns added aliases in order of addition
+    pub fn aliases(&self) -> &[String] {
+        &self.aliases
+    }
+
+    /// Hide the command from help and completion of its parent command, it is still dispatched to as a subcommand
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// let mut debug = Command::new("prints internal state", "", |_, _, _| Ok(()));
+    /// debug.hidden();
+    /// ```
+    pub fn hidden(&mut self) {
+        self.hidden = true;
+    }
+
+    /// Returns if the command is hidden from help and completion of its parent command
+    pub fn is_hidden(&self) -> bool {
+        self.hidden
+    }
+
+    /// Returns if the subcommand is named `name` or has `name` as an alias
+    fn is_named(&self, subcommand_name: &str, name: &str) -> bool {
+        subcommand_name == name || self.aliases.iter().any(|x| x == name)
+    }
+
+    /// Returns the full path of command names from the root command, e.g. `hello remote add`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// let mut command = Command::new("manages greetings", "hello COMMAND", |_, _, _| Ok(())).name("hello");
+    /// command.add_subcommand("say", Command::new("gives a friendly hello", "TEXT", |_, _, _| Ok(())));
+    /// let (_, subcommand) = command.subcommands().next().unwrap();
+    ///
+    /// assert_eq!("hello say", subcommand.full_path());
+    /// ```
+    pub fn full_path(&self) -> String {
+        self.name.clone()
+    }
+
+    /// Returns the usage line in help, which is prefixed with the full path for a subcommand
+    pub fn usage_line(&self) -> String {
+        let usage = self.variadic_usage();
+        if self.is_subcommand {
+            format!("{} {}", self.full_path(), usage)
+                .trim_end()
+                .to_string()
+        } else {
+            usage
+        }
+    }
+
+    /// Returns the usage with the last declared positional shown like `FILE...` when `args_variadic` is set
+    fn variadic_usage(&self) -> String {
+        let name = match self.positionals.last() {
+            Some((name, _)) if self.variadic => name,
+            _ => return self.usage.clone(),
+        };
+        let mut words: Vec<String> = self.usage.split(' ').map(String::from).collect();
+        if let Some(word) = words.iter_mut().rev().find(|x| *x == name) {
+            word.push_str("...");
+        }
+
+        words.join(" ")
+    }
+
+    /// Returns the environment variable name which a flag falls back to, set by `Flag::env` or derived from `env_prefix`
+    ///
+    /// # Arguments
+    ///
+    /// `flag` - The flag
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::{command::Command, flag::Flag};
+    /// let mut command = Command::new("serves greetings", "hello", |_, _, _| Ok(()));
+    /// command.env_prefix("HELLO");
+    ///
+    /// assert_eq!(
+    ///     Some(String::from("HELLO_MAX_SIZE")),
+    ///     command.flag_env(&Flag::new_int("m", "max-size", "max size of a greeting"))
+    /// );
+    /// ```
+    pub fn flag_env(&self, flag: &Flag) -> Option<String> {
+        env_name(flag, self.env_prefix.as_deref())
+    }
+
+    /// Returns names and commands of added subcommands in order of addition
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// # let mut command = Command::new("manages greetings", "hello COMMAND", |_, _, _| Ok(()));
+    /// command.add_subcommand("say", Command::new("gives a friendly hello", "TEXT", |_, _, _| Ok(())));
+    /// let names: Vec<&str> = command.subcommands().map(|(name, _)| name).collect();
+    /// assert_eq!(names, vec!["say"]);
+    /// ```
+    pub fn subcommands(&self) -> impl Iterator<Item = (&str, &Command)> {
+        self.subcommands
+            .iter()
+            .map(|(name, command)| (&name[..], command))
+    }
+
+    /// Set the subcommand to dispatch to when no subcommand name is given
+    ///
+    /// The first positional and args after it are passed to the default subcommand when it matches no subcommand,
+    /// so positionals are not shadowed
+    ///
+    /// # Arguments
+    ///
+    /// `name` - A string slice that holds the name of an added subcommand
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// # let mut command = Command::new("manages greetings", "hello COMMAND", |_, _, _| Ok(()));
+    /// # command.add_subcommand("say", Command::new("gives a friendly hello", "TEXT", |_, _, _| Ok(())));
+    /// command.default_subcommand("say");
+    /// ```
+    pub fn default_subcommand(&mut self, name: &str) {
+        self.default_subcommand = Some(String::from(name));
+    }
+
+    /// Set the handler which the first positional and args after it are passed to when it matches no subcommand,
+    /// for plugin-style commands like `hello foo` running `hello-foo`
+    ///
+    /// Flags before the name are parsed by the command, the default subcommand takes precedence when set
+    ///
+    /// # Arguments
+    ///
+    /// `handler` - A boxed function which is called with the name and args after it
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::process;
+    /// # use cancer::command::Command;
+    /// # let mut command = Command::new("manages greetings", "hello COMMAND", |_, _, _| Ok(()));
+    /// command.external_subcommand(Box::new(|name, args| {
+    ///     process::Command::new(format!("hello-{}", name)).args(args).status().unwrap();
+    /// }));
+    /// ```
+    pub fn external_subcommand(&mut self, handler: ExternalSubcommand) {
+        self.external_subcommand = Some(handler);
+    }
+
+    /// Add a hook which is called with parsed flags after args are parsed and validated but before `run`,
+    /// e.g. for initializing logging by a `--verbose` flag, hooks are called in order of registration
+    ///
+    /// # Arguments
+    ///
+    /// `hook` - A boxed function with flags parameter
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::{command::Command, flag::Flag};
+    /// # let mut command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
+    /// command.add_flag(Flag::new_bool("V", "verbose", "print more information"));
+    /// command.on_parsed(Box::new(|flags| {
+    ///     if flags.get_bool("verbose") == Some(true) {
+    ///         eprintln!("verbose logging enabled");
+    ///     }
+    /// }));
+    /// ```
+    pub fn on_parsed(&mut self, hook: ParsedHook) {
+        self.parsed_hooks.push(hook);
+    }
+
+    /// Set the prefixes of flag short and long identifiers, `-` and `--` by default
+    ///
+    /// Subcommands inherit the prefixes unless they set their own
+    ///
+    /// # Arguments
+    ///
+    /// `short` - A string slice that holds the prefix of short identifier
+    ///
+    /// `long` - A string slice that holds the prefix of long identifier
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// # let mut command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
+    /// command.flag_prefixes("/", "//");
+    /// ```
+    pub fn flag_prefixes(&mut self, short: &str, long: &str) {
+        self.set_flag_prefixes(short, long);
+        self.flag_prefixes_explicit = true;
+    }
+
+    /// Convert camelCase long flags to kebab-case before matching, e.g. `--maxSize` matches `max-size`
+    ///
+    /// Acronyms are kept as one word, e.g. `--maxHTTP` matches `max-http`, short flags are not affected
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// # let mut command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
+    /// command.normalize_flags();
+    /// ```
+    pub fn normalize_flags(&mut self) {
+        self.normalize_flags = true;
+    }
+
+    /// Strip accents from long flags and their identifiers before matching, e.g. `--café` matches `cafe`
+    ///
+    /// Both composed and decomposed accents are stripped, case is preserved, short flags are not affected
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// # let mut command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
+    /// command.normalize_unicode();
+    /// ```
+    pub fn normalize_unicode(&mut self) {
+        self.normalize_unicode = true;
+    }
+
+    /// Replace a `-` positional arg by the text read from stdin, or the input set by `input`, with whitespace trimmed
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// # let mut command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
+    /// command.dash_stdin();
+    /// ```
+    pub fn dash_stdin(&mut self) {
+        self.dash_stdin = true;
+    }
+
+    /// Split positional args like `key=value` into pairs delivered by `Args::pairs`, positional args without `=` are kept
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::io::Write;
+    /// # use cancer::command::Command;
+    /// let mut command = Command::new("sets variables", "set KEY=VALUE...", |args, _, out| {
+    ///     for (key, value) in args.pairs.iter() {
+    ///         writeln!(out, "{} is {}", key, value)?;
+    ///     }
+    ///     Ok(())
+    /// });
+    /// command.kv_positionals();
+    /// command.args_variadic();
+    /// ```
+    pub fn kv_positionals(&mut self) {
+        self.kv_positionals = true;
+    }
+
+    /// Read the single positional arg from stdin, or the input set by `input`, when no positional is given
+    /// and stdin is piped instead of showing help, with whitespace trimmed
+    ///
+    /// Help is shown as usual when stdin is a terminal
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// # let mut command = Command::new("encodes text", "encode TEXT", |_, _, _| Ok(()));
+    /// command.stdin_fallback();
+    /// ```
+    pub fn stdin_fallback(&mut self) {
+        self.stdin_fallback = true;
+    }
+
+    /// Require at least one flag of a group to be set, like `one of --input, --stdin is required` otherwise
+    ///
+    /// # Arguments
+    ///
+    /// `names` - A slice of string slice that holds short or long identifiers of the flags
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::{command::Command, flag::Flag};
+    /// # let mut command = Command::new("gives a friendly hello", "hello", |_, _, _| Ok(()));
+    /// command.add_flag(Flag::new_string("i", "input", "file to read the name from"));
+    /// command.add_flag(Flag::new_bool("s", "stdin", "read the name from stdin"));
+    /// command.require_one_of(&["input", "stdin"]);
+    /// ```
+    pub fn require_one_of(&mut self, names: &[&str]) {
+        self.required_groups
+            .push(names.iter().map(|x| String::from(*x)).collect());
+    }
+
+    /// Prompt for the values of required flags which are not set after parsing, like `Enter value for --name: `,
+    /// when stdin is a terminal
+    ///
+    /// An empty line leaves the flag unset, missing required flags are errors as usual when stdin is not a terminal
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::{command::Command, flag::Flag};
+    /// # let mut command = Command::new("gives a friendly hello", "hello", |_, _, _| Ok(()));
+    /// command.add_flag(Flag::new_string("n", "name", "name to greet").required());
+    /// command.interactive();
+    /// ```
+    pub fn interactive(&mut self) {
+        self.interactive = true;
+    }
+
+    /// Set the prefix of environment variables which flags fall back to when not provided in args
+    ///
+    /// For prefix `APP`, flag `--max-size` falls back to `APP_MAX_SIZE`, an environment variable set by `Flag::env` takes precedence
+    ///
+    /// # Arguments
+    ///
+    /// `prefix` - A string slice that holds the prefix
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// # let mut command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
+    /// command.env_prefix("HELLO");
+    /// ```
+    pub fn env_prefix(&mut self, prefix: &str) {
+        self.env_prefix = Some(String::from(prefix));
+    }
+
+    /// Set the loader of a config file which flags fall back to, args and environment variables take precedence
+    ///
+    /// Config keys are matched to flag long identifiers, keys matching no flag are ignored
+    ///
+    /// # Arguments
+    ///
+    /// `loader` - A boxed `ConfigLoader` object
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::{command::Command, config::JsonConfigLoader};
+    /// # let mut command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
+    /// command.config_loader(Box::new(JsonConfigLoader::new("hello.json")));
+    /// ```
+    pub fn config_loader(&mut self, loader: Box<dyn ConfigLoader>) {
+        self.config_loader = Some(loader);
+    }
+
+    /// Add a JSON config file which flags fall back to, the file is skipped when missing
+    ///
+    /// Config files are loaded after the config loader in the order added, so values of later files override earlier ones,
+    /// args and environment variables take precedence over all of them
+    ///
+    /// # Arguments
+    ///
+    /// `path` - The path of the JSON file
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// # let mut command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
+    /// command.add_config_file("/etc/hello.json");
+    /// command.add_config_file("hello.json");
+    /// ```
+    pub fn add_config_file(&mut self, path: impl Into<PathBuf>) {
+        self.config_files
+            .push(JsonConfigLoader::new(path).optional());
+    }
+
+    /// Add a JSON config file like `add_config_file`, which fails the command when missing
+    ///
+    /// # Arguments
+    ///
+    /// `path` - The path of the JSON file
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// # let mut command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
+    /// command.add_config_file_required("hello.json");
+    /// ```
+    pub fn add_config_file_required(&mut self, path: impl Into<PathBuf>) {
+        self.config_files.push(JsonConfigLoader::new(path));
+    }
+
+    /// Expand `@path` args to the whitespace-separated tokens of the file before parsing, like compilers do
+    ///
+    /// Quotes keep `"a b"` or `'a b'` as one token, args after `--` and values of flags are not expanded,
+    /// `@@` escapes a literal `@`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// # let mut command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
+    /// command.response_files();
+    /// ```
+    pub fn response_files(&mut self) {
+        self.response_files = true;
+    }
+
+    /// Set the command to take exactly `n` positional args
+    ///
+    /// # Arguments
+    ///
+    /// `n` - The count of positional args
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// # let mut command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
+    /// command.args_exact(1);
+    /// ```
+    pub fn args_exact(&mut self, n: usize) {
+        self.arity = Some(Arity::Exact(n));
+    }
+
+    /// Set the command to take at least `n` positional args
+    ///
+    /// # Arguments
+    ///
+    /// `n` - The minimum count of positional args
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// # let mut command = Command::new("gives a friendly hello", "hello TEXT...", |_, _, _| Ok(()));
+    /// command.args_min(1);
+    /// ```
+    pub fn args_min(&mut self, n: usize) {
+        self.arity = Some(Arity::Min(n));
+    }
+
+    /// Set the command to take between `lo` and `hi` positional args, inclusive
+    ///
+    /// # Arguments
+    ///
+    /// `lo` - The minimum count of positional args
+    ///
+    /// `hi` - The maximum count of positional args
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// # let mut command = Command::new("gives a friendly hello", "hello TEXT [TEXT]", |_, _, _| Ok(()));
+    /// command.args_range(1, 2);
+    /// ```
+    pub fn args_range(&mut self, lo: usize, hi: usize) {
+        self.arity = Some(Arity::Range(lo, hi));
+    }
+
+    /// Set the command to take any count of positional args, all of them are passed to `run`
+    ///
+    /// The last declared positional repeats, so extra args are converted to its type and usage shows it like `FILE...`,
+    /// combine with `args_min` or `args_range` to bound the count
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::{command::Command, flag::FlagValue};
+    /// let mut command = Command::new("removes files", "rm FILE", |_, _, _| Ok(()));
+    /// command.positional("FILE", FlagValue::String(None));
+    /// command.args_variadic();
+    /// assert_eq!("rm FILE...", command.usage_line());
+    /// ```
+    pub fn args_variadic(&mut self) {
+        self.variadic = true;
+        if self.arity.is_none() {
+            self.arity = Some(Arity::Min(0));
+        }
+    }
+
+    /// Declare the next positional arg with its type, which the positional is converted to before `run` is called
+    ///
+    /// # Arguments
+    ///
+    /// `name` - A string slice that holds the name of the positional used in error messages
+    ///
+    /// `kind` - A `FlagValue` whose variant is the type of the positional
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::{command::Command, flag::FlagValue};
+    /// let mut command = Command::new("adds two integers", "add A B", |args, _, _| {
+    ///     if let [FlagValue::Int(Some(a)), FlagValue::Int(Some(b))] = &args[..] {
+    ///         println!("{}", a + b);
+    ///     }
+    ///     Ok(())
+    /// });
+    /// command.positional("a", FlagValue::Int(None));
+    /// command.positional("b", FlagValue::Int(None));
+    /// ```
+    pub fn positional(&mut self, name: &str, kind: FlagValue) {
+        self.positionals.push((String::from(name), kind));
+    }
+
+    /// Reset every flag to its default value, so parsed values do not leak between executions
+    ///
+    /// Global flags inherited from the parent command keep their values
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// # let mut command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
+    /// command.reset_flags();
+    /// ```
+    pub fn reset_flags(&mut self) {
+        for flag in self.flags.iter_mut() {
+            flag.reset();
+        }
+    }
+
+    /// Set the writer which help and version information is written to, `stdout` by default
+    ///
+    /// Errors are written to the error writer set by `error_writer`, subcommands inherit the writer unless they set their own
+    ///
+    /// # Arguments
+    ///
+    /// `w` - A boxed `Write` object
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::io;
+    /// # use cancer::command::Command;
+    /// # let mut command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
+    /// command.writer(Box::new(io::stderr()));
+    /// ```
+    pub fn writer(&mut self, w: Box<dyn Write>) {
+        self.writer = w;
+        self.writer_is_stdout = false;
+    }
+
+    /// Set the input which a `-` positional arg is read from when `dash_stdin` is set, `stdin` by default
+    ///
+    /// # Arguments
+    ///
+    /// `r` - A boxed `Read` object
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::io;
+    /// # use cancer::command::Command;
+    /// # let mut command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
+    /// command.input(Box::new(io::empty()));
+    /// ```
+    pub fn input(&mut self, r: Box<dyn Read>) {
+        self.input = r;
+        self.input_is_stdin = false;
+    }
+
+    /// Pipe help into the pager of the `PAGER` environment variable, `less -R` by default
+    ///
+    /// Help is printed directly when `stdout` is not a terminal, the writer is not `stdout` or no pager is available
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// # let mut command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
+    /// command.paged_help();
+    /// ```
+    pub fn paged_help(&mut self) {
+        self.paged_help = true;
+    }
+
+    /// Set whether flags are listed alphabetically by long identifier in help, help and version are listed last
+    ///
+    /// Flags are listed in order of addition by default, parsing is not affected
+    ///
+    /// # Arguments
+    ///
+    /// `enabled` - A bool that holds whether flags are sorted
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// # let mut command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
+    /// command.sort_flags(true);
+    /// ```
+    pub fn sort_flags(&mut self, enabled: bool) {
+        self.sort_flags = enabled;
+    }
+
+    /// Add the `--help-all` flag, which prints help including hidden flags annotated as hidden or deprecated
+    ///
+    /// `--help` still omits hidden flags
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::{command::Command, flag::Flag};
+    /// # let mut command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
+    /// command.add_flag(Flag::new_bool("", "debug", "debug output").hidden());
+    /// command.enable_help_all();
+    /// ```
+    pub fn enable_help_all(&mut self) {
+        let description = format!("help for {} including hidden flags", self.name);
+        self.add_flag(
+            Flag::new_long(HELP_ALL_LONG, &description, FlagValue::Bool(false)).reserved(),
+        );
+    }
+
+    /// Returns if help is being rendered for `--help-all`, so renders include hidden flags
+    pub fn is_help_all(&self) -> bool {
+        self.help_all
+    }
+
+    /// Returns flags in the order listed in help, sorted when `sort_flags` is set
+    ///
+    /// # Arguments
+    ///
+    /// `flags` - A slice of flags, e.g. command flags or global flags
+    pub(crate) fn help_order<'a>(&self, flags: &'a [Flag]) -> Vec<&'a Flag> {
+        let mut flags: Vec<&Flag> = flags.iter().collect();
+        if self.sort_flags {
+            flags.sort_by_key(|x| {
+                let name = if x.long.is_empty() { &x.short } else { &x.long };
+                (is_reserved(x), name.to_lowercase())
+            });
+        }
+
+        flags
+    }
+
+    /// Set the locale of help text, which help renders like `CatalogHelpRender` consult
+    ///
+    /// # Arguments
+    ///
+    /// `lang` - A string slice that holds the locale, e.g. `de`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// # let mut command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
+    /// command.locale("de");
+    /// ```
+    pub fn locale(&mut self, lang: &str) {
+        self.locale = Some(String::from(lang));
+    }
+
+    /// Returns the locale of help text if set
+    pub fn get_locale(&self) -> Option<&str> {
+        self.locale.as_deref()
+    }
+
+    /// Set the writer which errors are written to, `stderr` by default
+    ///
+    /// Subcommands inherit the error writer, error color and theme unless they set their own
+    ///
+    /// # Arguments
+    ///
+    /// `w` - A boxed `Write` object
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::io;
+    /// # use cancer::command::Command;
+    /// # let mut command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
+    /// command.error_writer(Box::new(io::sink()));
+    /// ```
+    pub fn error_writer(&mut self, w: Box<dyn Write>) {
+        self.error_writer = w;
+        self.error_writer_is_stderr = false;
+    }
+
+    /// Force errors to be colored red or not, by default errors are colored when `stderr` is a terminal
+    ///
+    /// Color is suppressed when the `NO_COLOR` environment variable is present unless forced on
+    ///
+    /// # Arguments
+    ///
+    /// `color` - A bool that holds whether errors are colored
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// # let mut command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
+    /// command.error_color(false);
+    /// ```
+    pub fn error_color(&mut self, color: bool) {
+        self.error_color = Some(color);
+    }
+
+    /// Set the colors of help and errors, `Theme::default()` by default
+    ///
+    /// # Arguments
+    ///
+    /// `theme` - A `Theme` that holds the colors
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::{command::Command, theme::Theme};
+    /// # let mut command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
+    /// command.theme(Theme::no_color());
+    /// ```
+    pub fn theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    /// Returns the colors of help and errors
+    pub fn get_theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    /// Set the formatter which errors are rendered by, instead of the default `prog: message`
+    ///
+    /// Rendered errors are written as is, neither colored nor followed by the usage footer
+    ///
+    /// # Arguments
+    ///
+    /// `f` - A boxed function which returns the text of an error
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// # let mut command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
+    /// command.error_render(Box::new(|err| format!("error: {}", err)));
+    /// ```
+    pub fn error_render(&mut self, f: ErrorRender) {
+        self.error_render = Some(f);
+    }
+
+    /// Set whether parse decisions are written to the error writer while args are parsed, `false` by default
+    ///
+    /// Each arg is traced on its own line prefixed with `trace: ` like `explain`, e.g. `trace: -f: flag --ferris = Bool(true)`
+    ///
+    /// # Arguments
+    ///
+    /// `enabled` - A bool that holds whether parsing is traced
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// # let mut command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
+    /// command.trace(true);
+    /// ```
+    pub fn trace(&mut self, enabled: bool) {
+        self.trace = enabled;
+    }
+
+    /// Set whether the usage footer printed after parse errors ends with a hint like `Try 'hello --help' for more information.`
+    ///
+    /// # Arguments
+    ///
+    /// `enabled` - A bool that holds whether the hint is printed, `true` by default
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// # let mut command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
+    /// command.help_hint(false);
+    /// ```
+    pub fn help_hint(&mut self, enabled: bool) {
+        self.help_hint = enabled;
+    }
+
+    /// Set whether parse errors are followed by the short usage footer, only the error line is printed when disabled
+    ///
+    /// # Arguments
+    ///
+    /// `enabled` - A bool that holds whether the footer is printed, `true` by default
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// # let mut command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
+    /// command.print_usage_on_error(false);
+    /// ```
+    pub fn print_usage_on_error(&mut self, enabled: bool) {
+        self.print_usage_on_error = enabled;
+    }
+
+    /// Set whether parse errors are followed by the full help instead of the short usage footer
+    ///
+    /// # Arguments
+    ///
+    /// `enabled` - A bool that holds whether the full help is printed, `false` by default
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// # let mut command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
+    /// command.help_on_error(true);
+    /// ```
+    pub fn help_on_error(&mut self, enabled: bool) {
+        self.help_on_error = enabled;
+    }
+
+    /// Returns the short usage footer printed after parse errors, like GNU tools
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// let command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(())).name("hello");
+    ///
+    /// assert_eq!(
+    ///     "Usage: hello TEXT\nTry 'hello --help' for more information.",
+    ///     command.short_usage()
+    /// );
+    /// ```
+    pub fn short_usage(&self) -> String {
+        let mut text = format!("Usage: {}", self.usage_line());
+        if self.help_hint {
+            text.push_str(&format!(
+                "\nTry '{} {}{}' for more information.",
+                self.name, self.flag_long_start, HELP_LONG
+            ));
+        }
+
+        text
+    }
+
+    /// Add a usage example which is rendered in the examples section of help
+    ///
+    /// # Arguments
+    ///
+    /// `text` - A string slice that holds the example command line
+    ///
+    /// `description` - A string slice that holds the description of the example
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// # let mut command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
+    /// command.add_example("hello world", "gives a hello to world");
+    /// ```
+    pub fn add_example(&mut self, text: &str, description: &str) {
+        self.examples
+            .push((String::from(text), String::from(description)));
+    }
+
+    /// Returns added usage examples with their descriptions in order of addition
+    pub fn examples(&self) -> &[(String, String)] {
+        &self.examples
+    }
+
+    /// Add an extra version line, which `DefaultVersionRender` appends after the main line when the `verbose` flag is set
+    ///
+    /// # Arguments
+    ///
+    /// `text` - A string slice that holds the version line, e.g. the version of a dependency
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::{command::Command, flag::Flag};
+    /// # let mut command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
+    /// command.add_flag(Flag::new_bool("V", "verbose", "verbose output"));
+    /// command.add_version_line("ferris-says 0.2.1");
+    /// ```
+    pub fn add_version_line(&mut self, text: &str) {
+        self.version_lines.push(String::from(text));
+    }
+
+    /// Returns added extra version lines in order of addition
+    pub fn version_lines(&self) -> &[String] {
+        &self.version_lines
+    }
+
+    /// Set the render of help text, `DefaultHelpRender` by default, the render can be swapped at any time before help is printed
+    ///
+    /// # Arguments
+    ///
+    /// `render` - A boxed `HelpRender` object
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::{command::Command, help::DefaultHelpRender};
+    /// # let mut command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
+    /// command.set_help_render(Box::new(DefaultHelpRender::new().color(true)));
+    /// ```
+    pub fn set_help_render(&mut self, render: Box<dyn HelpRender>) {
+        self.help_render = Some(render);
+    }
+
+    /// Set the render of help text to a `TemplateHelpRender` with the template provided
+    ///
+    /// # Arguments
+    ///
+    /// `template` - A string slice that holds the template with placeholders like `{usage}` and `{flags}`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// # let mut command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
+    /// command.help_template("Usage: {usage}\n\n{flags}\n\n{description}");
+    /// ```
+    pub fn help_template(&mut self, template: &str) {
+        self.set_help_render(Box::new(TemplateHelpRender::new(template)));
+    }
+
+    /// Set the render of version text, `DefaultVersionRender` by default, the render can be swapped at any time before version is printed
+    ///
+    /// # Arguments
+    ///
+    /// `render` - A boxed `VersionRender` object
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::{command::Command, version::RawVersionRender};
+    /// # let mut command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
+    /// command.set_version_render(Box::new(RawVersionRender::new()));
+    /// ```
+    pub fn set_version_render(&mut self, render: Box<dyn VersionRender>) {
+        self.version_render = Some(render);
+    }
+
+    /// Returns the help text rendered by the currently set help render
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// let command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
+    ///
+    /// assert!(command.help_string().starts_with("gives a friendly hello\n"));
+    /// ```
+    pub fn help_string(&self) -> String {
+        match &self.help_render {
+            Some(render) => render.help_text(self),
+            None => String::new(),
+        }
+    }
+
+    /// Returns the completion script of the command for a shell, which completes subcommands and flags
+    ///
+    /// # Arguments
+    ///
+    /// `shell` - The shell which the script is written for
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::{command::Command, completion::Shell};
+    /// let command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(())).name("hello");
+    ///
+    /// assert!(command.completion(Shell::Zsh).starts_with("#compdef hello\n"));
+    /// ```
+    pub fn completion(&self, shell: Shell) -> String {
+        completion_script(self, shell)
+    }
+
+    /// Write the completion script of the command for a shell to a conventional file name in a directory,
+    /// `_app` for zsh, `app.bash` for bash and `app.fish` for fish
+    ///
+    /// # Arguments
+    ///
+    /// `shell` - The shell which the script is written for
+    ///
+    /// `dir` - The directory which the script is written to
+    ///
+    /// # Return
+    ///
+    /// The path of the written file, or an IO error
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::path::Path;
+    /// # use cancer::{command::Command, completion::Shell};
+    /// let command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(())).name("hello");
+    /// let path = command
+    ///     .install_completion(Shell::Fish, Path::new("/usr/share/fish/vendor_completions.d"))
+    ///     .unwrap();
+    /// ```
+    pub fn install_completion(&self, shell: Shell, dir: &Path) -> io::Result<PathBuf> {
+        let path = dir.join(shell.file_name(&self.name));
+        fs::write(&path, self.completion(shell))?;
+        Ok(path)
+    }
+
+    /// Enable the hidden `completion <shell>` subcommand, which prints the completion script for `bash`, `zsh` or `fish`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// # let mut command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
+    /// command.enable_completion_command();
+    /// ```
+    pub fn enable_completion_command(&mut self) {
+        self.completion_command = true;
+    }
+}
+
+impl Command {
+    /// Execute command, exit the process with a nonzero code when the command fails
+    ///
+    /// Help is printed when `-h` or `--help` is passed and version when `-v` or `--version` is passed,
+    /// help always wins over version when both are passed regardless of their order
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use cancer::command::Command;
+    /// let mut command = Command::new("gives a friendly hello", "hello TEXT", |args, flags, _| {
+    ///     todo!();
+    /// });
+    /// command.execute();
+    /// ```
+    pub fn execute(&mut self) {
+        self.execute_with(get_args());
+    }
+
+    /// Execute command with the arguments provided, exit the process with a nonzero code when the command fails
+    ///
+    /// # Arguments
+    ///
+    /// `args` - A vector of string that holds arguments, including the program name
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use cancer::command::Command;
+    /// let mut command = Command::new("gives a friendly hello", "hello TEXT", |args, flags, _| {
+    ///     todo!();
+    /// });
+    /// command.execute_with(vec![String::from("hello"), String::from("world")]);
+    /// ```
+    pub fn execute_with(&mut self, args: Vec<String>) {
+        match self.dispatch(args) {
+            Err(_) => (self.exit)(self.error_exit_code),
+            Ok(_) if self.help_shown && self.help_exit_code != 0 => {
+                (self.exit)(self.help_exit_code)
+            }
+            Ok(_) => {}
+        }
+    }
+
+    /// Set the code which `execute` exits the process with after help or version is printed
+    ///
+    /// `try_execute` is not affected
+    ///
+    /// # Arguments
+    ///
+    /// `code` - An exit code, `0` by default
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// # let mut command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
+    /// command.help_exit_code(0);
+    /// ```
+    pub fn help_exit_code(&mut self, code: i32) {
+        self.help_exit_code = code;
+    }
+
+    /// Set the code which `execute` exits the process with when the command fails, e.g. `2` like GNU tools
+    ///
+    /// `try_execute` is not affected
+    ///
+    /// # Arguments
+    ///
+    /// `code` - An exit code, `1` by default
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// # let mut command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
+    /// command.error_exit_code(2);
+    /// ```
+    pub fn error_exit_code(&mut self, code: i32) {
+        self.error_exit_code = code;
+    }
+
+    /// Execute command with the arguments provided and returns the exit code on failure instead of exiting the process
+    ///
+    /// # Arguments
+    ///
+    /// `args` - A vector of string that holds arguments, including the program name
+    ///
+    /// # Return
+    ///
+    /// `Ok(())` on success, or `Err` with a nonzero exit code when the command fails
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// let mut command = Command::new("gives a friendly hello", "hello TEXT", |args, flags, _| {
+    ///     Err("something went wrong".into())
+    /// });
+    /// let result = command.try_execute(vec![String::from("hello"), String::from("world")]);
+    ///
+    /// assert_eq!(Err(1), result);
+    /// ```
+    pub fn try_execute(&mut self, args: Vec<String>) -> Result<(), i32> {
+        self.dispatch(args).map_err(|_| ERROR_EXIT_CODE)
+    }
+
+    /// Dispatch command with the arguments provided, returns the error after printing it
+    ///
+    /// Like `try_execute`, help, version and subcommands are handled, flags are reset and parsed from args only,
+    /// so the command can be dispatched again with other args, e.g. by a REPL or a test, the process is never exited
+    ///
+    /// # Arguments
+    ///
+    /// `argv` - A vector of string that holds arguments, including the program name
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use cancer::command::Command;
+    /// # use cancer::error::CommandError;
+    /// let mut command = Command::new("gives a friendly hello", "hello TEXT", |args, _, _| {
+    ///     println!("hello, {}!", args[0]);
+    ///     Ok(())
+    /// });
+    ///
+    /// assert!(command.dispatch(vec![String::from("hello"), String::from("world")]).is_ok());
+    /// assert!(matches!(
+    ///     command.dispatch(vec![String::from("hello"), String::from("--foo")]),
+    ///     Err(CommandError::Parse(_))
+    /// ));
+    /// ```
+    pub fn dispatch(&mut self, argv: Vec<String>) -> Result<(), CommandError> {
+        let args = match self.prepare_run(argv, false)? {
+            Some(args) => args,
+            None => return Ok(()),
+        };
+        self.call_run(args).map_err(|err| {
+            let err = CommandError::Run(err);
+            self.eprint_command_error(&err);
+            err
+        })
+    }
+
+    /// Execute command with the arguments provided like `try_execute`, then await the async logic set by `new_async`
+    ///
+    /// The returned future is awaited on any runtime, the logic set by `new` is called synchronously,
+    /// subcommands are executed synchronously
+    ///
+    /// # Arguments
+    ///
+    /// `args` - A vector of string that holds arguments, including the program name
+    ///
+    /// # Return
+    ///
+    /// `Ok(())` on success, or `Err` with a nonzero exit code when the command fails
+    #[cfg(feature = "async")]
+    pub async fn execute_async(&mut self, args: Vec<String>) -> Result<(), i32> {
+        let args = match self.prepare_run(args, false).map_err(|_| ERROR_EXIT_CODE)? {
+            Some(args) => args,
+            None => return Ok(()),
+        };
+        let result = match self.run_async {
+            Some(run) => {
+                let flags = self.get_flags();
+                for hook in self.parsed_hooks.iter() {
+                    hook(&flags);
+                }
+                let mut writer = std::mem::replace(&mut self.writer, Box::new(io::sink()));
+                let result = run(args, self.get_flags(), &mut writer)
+                    .await
+                    .and_then(|_| writer.flush().map_err(Into::into));
+                self.writer = writer;
+                result
+            }
+            None => self.call_run(args),
+        };
+        if let Err(err) = result {
+            self.eprint_command_error(&CommandError::Run(err));
+            return Err(ERROR_EXIT_CODE);
+        }
+
+        Ok(())
+    }
+
+    /// Run command once with the arguments provided, returns errors instead of printing them
+    ///
+    /// Unlike `execute`, which is the process-level entry point, flags are reset and parsed from args and config files
+    /// without environment variables, then `run` of the command or the subcommand is called with positional args,
+    /// help and version are not handled and nothing is printed
+    ///
+    /// # Arguments
+    ///
+    /// `args` - A vector of string that holds arguments, including the program name
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::io::Write;
+    /// # use cancer::command::Command;
+    /// let mut command = Command::new("gives a friendly hello", "hello TEXT", |args, flags, out| {
+    ///     writeln!(out, "hello, {}!", args[0])?;
+    ///     Ok(())
+    /// });
+    /// let result = command.run_once(vec![String::from("hello"), String::from("world")]);
+    ///
+    /// assert!(result.is_ok());
+    /// ```
+    pub fn run_once(&mut self, args: Vec<String>) -> Result<(), CommandError> {
+        match self.prepare_run(args, true)? {
+            Some(args) => self.call_run(args).map_err(CommandError::Run),
+            None => Ok(()),
+        }
+    }
+
+    /// Run command once with the arguments and input provided, like `run_once` without touching the process stdin
+    ///
+    /// A `-` positional arg is read from the input when `dash_stdin` is set, the input is read up front
+    /// and replaces the input of the command only for this run
+    ///
+    /// # Arguments
+    ///
+    /// `args` - A vector of string that holds arguments, including the program name
+    ///
+    /// `input` - A `Read` object which the command reads from during this run
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::io::Cursor;
+    /// # use cancer::command::Command;
+    /// let mut command = Command::new("gives a friendly hello", "hello TEXT", |args, _, _| {
+    ///     println!("hello, {}!", args[0]);
+    ///     Ok(())
+    /// });
+    /// command.dash_stdin();
+    /// let result = command.run_with_input(
+    ///     vec![String::from("hello"), String::from("-")],
+    ///     Cursor::new("world"),
+    /// );
+    ///
+    /// assert!(result.is_ok());
+    /// ```
+    pub fn run_with_input(
+        &mut self,
+        args: Vec<String>,
+        mut input: impl Read,
+    ) -> Result<(), CommandError> {
+        let mut bytes = vec![];
+        input
+            .read_to_end(&mut bytes)
+            .map_err(|err| CommandError::Parse(format!("failed reading stdin: {}", err)))?;
+
+        let input = std::mem::replace(&mut self.input, Box::new(Cursor::new(bytes)));
+        let input_is_stdin = std::mem::replace(&mut self.input_is_stdin, false);
+        let result = self.run_once(args);
+        self.input = input;
+        self.input_is_stdin = input_is_stdin;
+        result
+    }
+
+    /// Parse the args of the process without running the command, returns positional args and flags
+    ///
+    /// Useful when a program prefers imperative control flow over the `run` function,
+    /// args are parsed like `execute`, help, version and subcommands are handled and errors are printed
+    ///
+    /// # Return
+    ///
+    /// Positional args and the flags which `run` would receive, `None` when help or version was printed
+    /// or a subcommand was executed, or a parse error
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use cancer::command::Command;
+    /// let mut command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
+    /// if let Some((args, flags)) = command.parse_args().unwrap() {
+    ///     println!("hello, {}!", args[0]);
+    /// }
+    /// ```
+    pub fn parse_args(&mut self) -> Result<Option<(Args, FlagSet<'_>)>, CommandError> {
+        self.parse_args_with(get_args())
+    }
+
+    /// Parse the args provided without running the command, returns positional args and flags
+    ///
+    /// # Arguments
+    ///
+    /// `args` - A vector of string that holds arguments, including the program name
+    ///
+    /// # Return
+    ///
+    /// Positional args and the flags which `run` would receive, `None` when help or version was printed
+    /// or a subcommand was executed, or a parse error
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use cancer::{command::Command, flag::{Flag, FlagValue}};
+    /// let mut command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
+    /// command.add_flag(Flag::new_bool("f", "ferris", "say hello from ferris"));
+    /// let (args, flags) = command
+    ///     .parse_args_with(vec![String::from("hello"), String::from("-f"), String::from("world")])
+    ///     .unwrap()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(vec![FlagValue::String(Some(String::from("world")))], args.values);
+    /// assert_eq!(Some(true), flags.get_bool("ferris"));
+    /// ```
+    pub fn parse_args_with(
+        &mut self,
+        args: Vec<String>,
+    ) -> Result<Option<(Args, FlagSet<'_>)>, CommandError> {
+        match self.prepare_run(args, false)? {
+            Some(args) => Ok(Some((args, self.get_flags()))),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns a human-readable report of how args would be parsed without updating any flag or running the command
+    ///
+    /// Each arg is reported on its own line as the program name, a matched flag with its resolved value,
+    /// a positional or an unknown flag
+    ///
+    /// # Arguments
+    ///
+    /// `args` - A vector of string that holds arguments, including the program name
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use cancer::{command::Command, flag::Flag};
+    /// let mut command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
+    /// command.add_flag(Flag::new_bool("f", "ferris", "say hello from ferris"));
+    /// let report = command.explain(vec![
+    ///     String::from("hello"),
+    ///     String::from("-f"),
+    ///     String::from("world"),
+    /// ]);
+    ///
+    /// assert_eq!("hello: program\n-f: flag --ferris = Bool(true)\nworld: positional\n", report);
+    /// ```
+    pub fn explain(&self, args: Vec<String>) -> String {
+        let mut text = String::from("");
+
+        let parsed_args = self.parse(args.iter().map(|x| &x[..]).collect());
+        for (i, parsed_arg) in parsed_args.iter().enumerate() {
+            text.push_str(&self.describe(i, parsed_arg));
+        }
+
+        text
+    }
+
+    /// Check structural invariants of the command and its subcommands, without parsing any args
+    ///
+    /// Identifiers of flags are unique, names and aliases of subcommands are unique,
+    /// conflicts and requires of flags reference added flags, and long flags in examples are added,
+    /// e.g. `--name` in `hello --name world`
+    ///
+    /// # Return
+    ///
+    /// A descriptive error message of the first violated invariant
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::{command::Command, flag::Flag};
+    /// let mut command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
+    /// command.add_flag(Flag::new_bool("q", "quiet", "quiet output").conflicts_with("verbose"));
+    ///
+    /// assert_eq!(
+    ///     Err(String::from("flag --quiet conflicts with unknown flag verbose")),
+    ///     command.validate()
+    /// );
+    /// ```
+    pub fn validate(&self) -> Result<(), String> {
+        let flags: Vec<&Flag> = self.all_flags().collect();
+
+        for (i, flag) in flags.iter().enumerate() {
+            let name = flag.display_name(&self.flag_short_start, &self.flag_long_start);
+            for other in flags.iter().skip(i + 1) {
+                if other.is_named(&flag.short) {
+                    return Err(format!(
+                        "duplicate flag {}{}",
+                        self.flag_short_start, flag.short
+                    ));
+                }
+                if other.is_named(&flag.long) {
+                    return Err(format!(
+                        "duplicate flag {}{}",
+                        self.flag_long_start, flag.long
+                    ));
+                }
+            }
+            for (relation, names) in [
+                ("conflicts with", &flag.conflicts),
+                ("requires", &flag.requires),
+            ] {
+                if let Some(unknown) = names.iter().find(|x| !flags.iter().any(|y| y.is_named(x))) {
+                    return Err(format!(
+                        "flag {} {} unknown flag {}",
+                        name, relation, unknown
+                    ));
+                }
+            }
+        }
+        for group in self.required_groups.iter() {
+            if let Some(unknown) = group.iter().find(|x| !flags.iter().any(|y| y.is_named(x))) {
+                return Err(format!(
+                    "required group references unknown flag {}",
+                    unknown
+                ));
+            }
+        }
+        for (example, _) in self.examples.iter() {
+            if let Some(flag) = self.unknown_example_flag(example) {
+                return Err(format!(
+                    "example \"{}\" references unknown flag {}",
+                    example, flag
+                ));
+            }
+        }
+        let mut names: Vec<&str> = vec![];
+        for (name, subcommand) in self.subcommands.iter() {
+            for name in std::iter::once(name).chain(subcommand.aliases.iter()) {
+                if names.contains(&&name[..]) {
+                    return Err(format!("duplicate command {}", name));
+                }
+                names.push(name);
+            }
+        }
+        for (_, subcommand) in self.subcommands.iter() {
+            subcommand.validate()?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the first long flag in an example which is not added, flags after a subcommand name are looked up
+    /// in the subcommand along with inherited global flags
+    ///
+    /// # Arguments
+    ///
+    /// `example` - A string slice that holds the example, e.g. `hello --name world`
+    fn unknown_example_flag(&self, example: &str) -> Option<String> {
+        let mut command = self;
+        let mut global_flags: Vec<&Flag> = self.all_flags().filter(|x| x.global).collect();
+        for token in example.split_whitespace() {
+            if token == command.flag_long_start {
+                break;
+            }
+            let name = token.split_once('=').map_or(token, |(name, _)| name);
+            if !name.starts_with(&command.flag_long_start) || name == command.flag_long_start {
+                if let Some((_, subcommand)) = command
+                    .subcommands
+                    .iter()
+                    .find(|(name, subcommand)| subcommand.is_named(name, token))
+                {
+                    command = subcommand;
+                    global_flags.extend(command.flags.iter().filter(|x| x.global));
+                }
+                continue;
+            }
+            let flags: Vec<&Flag> = command
+                .flags
+                .iter()
+                .chain(global_flags.iter().copied())
+                .collect();
+            if !flags.iter().any(|x| command.is_match(x, name))
+                && command.parse_negated(&flags, name).is_none()
+            {
+                return Some(String::from(name));
+            }
+        }
+
+        None
+    }
+
+    /// Execute the subcommand at index with global flags inherited, and update global flags by its parsed values
+    ///
+    /// Writers, error color and theme are lent to the subcommand for the execution unless it set its own
+    ///
+    /// # Arguments
+    ///
+    /// `index` - The index of the subcommand
+    ///
+    /// `args` - A vector of string that holds arguments of the subcommand, including the subcommand name
+    ///
+    /// `once` - Whether the subcommand is run by `run_once` instead of dispatched
+    fn execute_subcommand(
+        &mut self,
+        index: usize,
+        args: Vec<String>,
+        once: bool,
+    ) -> Result<(), CommandError> {
+        let global_flags: Vec<Flag> = self.all_flags().filter(|x| x.global).cloned().collect();
+        let name = format!("{} {}", self.name, self.subcommands[index].0);
+        let subcommand = &mut self.subcommands[index].1;
+        subcommand.global_flags = global_flags;
+        let inherit_writer = subcommand.writer_is_stdout;
+        if inherit_writer {
+            std::mem::swap(&mut self.writer, &mut subcommand.writer);
+            std::mem::swap(&mut self.writer_is_stdout, &mut subcommand.writer_is_stdout);
+        }
+        let inherit_error_writer = subcommand.error_writer_is_stderr;
+        if inherit_error_writer {
+            std::mem::swap(&mut self.error_writer, &mut subcommand.error_writer);
+            std::mem::swap(
+                &mut self.error_writer_is_stderr,
+                &mut subcommand.error_writer_is_stderr,
+            );
+        }
+        let inherit_error_color = subcommand.error_color.is_none();
+        if inherit_error_color {
+            subcommand.error_color = self.error_color;
+        }
+        let theme = (subcommand.theme == Theme::default())
+            .then(|| std::mem::replace(&mut subcommand.theme, self.theme.clone()));
+        let args = [name].into_iter().chain(args.into_iter().skip(1)).collect();
+        let result = if once {
+            subcommand.run_once(args)
+        } else {
+            subcommand.dispatch(args)
+        };
+
+        if inherit_writer {
+            std::mem::swap(&mut self.writer, &mut subcommand.writer);
+            std::mem::swap(&mut self.writer_is_stdout, &mut subcommand.writer_is_stdout);
+        }
+        if inherit_error_writer {
+            std::mem::swap(&mut self.error_writer, &mut subcommand.error_writer);
+            std::mem::swap(
+                &mut self.error_writer_is_stderr,
+                &mut subcommand.error_writer_is_stderr,
+            );
+        }
+        if inherit_error_color {
+            subcommand.error_color = None;
+        }
+        if let Some(theme) = theme {
+            subcommand.theme = theme;
+        }
+        self.help_shown = subcommand.help_shown;
+        let global_flags = std::mem::take(&mut subcommand.global_flags);
+        for global_flag in global_flags {
+            if let Some(flag) = self.all_flags_mut().find(|x| x.long == global_flag.long) {
+                flag.value = global_flag.value;
+                flag.source = global_flag.source;
+            }
+        }
+
+        result
+    }
+
+    /// Returns if args ask for help by the help flag before `--`, so help is printed before any check of parent commands
+    ///
+    /// # Arguments
+    ///
+    /// `args` - A slice of string that holds arguments, including the command name
+    fn asks_help(&self, args: &[String]) -> bool {
+        if self.help_render.is_none() {
+            return false;
+        }
+        let helps: Vec<&Flag> = self
+            .flags
+            .iter()
+            .filter(|x| is_help(x) || is_help_all(x))
+            .collect();
+
+        args.iter()
+            .skip(1)
+            .take_while(|x| **x != self.flag_long_start)
+            .any(|x| helps.iter().any(|help| self.is_match(help, x)))
+    }
+
+    /// Returns if provided arg is a flag with the prefixes of command, a bare short prefix like `-` is a positional
+    fn is_flag(&self, arg: &str) -> bool {
+        arg != self.flag_short_start
+            && is_flag_with(arg, &self.flag_short_start, &self.flag_long_start)
+            && !self.is_negative_number(arg)
+    }
+
+    /// Returns if provided arg is a negative number like `-5` or `-1.5` whose first digit is no short flag
+    fn is_negative_number(&self, arg: &str) -> bool {
+        match arg.strip_prefix(&self.flag_short_start) {
+            Some(number) if !arg.starts_with(&self.flag_long_start) && is_number(number) => {
+                let first = &number[..1];
+                !self.all_flags().any(|x| x.short == first)
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns if provided arg match flag with the prefixes of command
+    ///
+    /// camelCase long flags are converted to kebab-case when flags are normalized,
+    /// accents are stripped from long flags and identifiers when unicode is normalized
+    fn is_match(&self, flag: &Flag, arg: &str) -> bool {
+        if flag.is_match_with(arg, &self.flag_short_start, &self.flag_long_start) {
+            return true;
+        }
+        let long = match arg.strip_prefix(&self.flag_long_start) {
+            Some(long) if !flag.long.is_empty() => long,
+            _ => return false,
+        };
+        match (self.normalize_flags, self.normalize_unicode) {
+            (true, true) => strip_accents(&kebab_case(long)) == strip_accents(&flag.long),
+            (true, false) => kebab_case(long) == flag.long,
+            (false, true) => strip_accents(long) == strip_accents(&flag.long),
+            (false, false) => false,
+        }
+    }
+
+    /// Returns added flags followed by global flags inherited from the parent command
+    fn all_flags(&self) -> impl Iterator<Item = &Flag> {
+        self.flags.iter().chain(self.global_flags.iter())
+    }
+
+    /// Returns mutable added flags followed by global flags inherited from the parent command
+    fn all_flags_mut(&mut self) -> impl Iterator<Item = &mut Flag> {
+        self.flags.iter_mut().chain(self.global_flags.iter_mut())
+    }
+
+    /// Split args into the args of the command and the subcommand to dispatch to with its args
+    ///
+    /// # Arguments
+    ///
+    /// `args` - A vector of string that holds arguments, including the program name
+    ///
+    /// # Return
+    ///
+    /// A tuple of the args of the command and an optional tuple of the subcommand index and its args
+    fn split_subcommand(
+        &self,
+        mut args: Vec<String>,
+    ) -> (Vec<String>, Option<(usize, Vec<String>)>) {
+        if self.subcommands.is_empty() {
+            return (args, None);
+        }
+
+        let position = self.first_positional(&args);
+        if let Some(arg) = args.get(position) {
+            if let Some(index) = self
+                .subcommands
+                .iter()
+                .position(|(name, subcommand)| subcommand.is_named(name, arg))
+            {
+                let subcommand_args = args.split_off(position);
+                return (args, Some((index, subcommand_args)));
+            }
+        }
+        if let Some(default_subcommand) = &self.default_subcommand {
+            if let Some(index) = self
+                .subcommands
+                .iter()
+                .position(|(name, _)| name == default_subcommand)
+            {
+                let mut subcommand_args = vec![default_subcommand.clone()];
+                subcommand_args.extend(args.split_off(position));
+                return (args, Some((index, subcommand_args)));
+            }
+        }
+
+        (args, None)
+    }
+
+    /// Split args into the args of the command and the first positional with args after it for the external subcommand
+    ///
+    /// # Arguments
+    ///
+    /// `args` - A vector of string that holds arguments, including the program name
+    ///
+    /// # Return
+    ///
+    /// A tuple of the args of the command and optional args of the external subcommand, including its name
+    fn split_external(&self, mut args: Vec<String>) -> (Vec<String>, Option<Vec<String>>) {
+        if self.external_subcommand.is_none() {
+            return (args, None);
+        }
+
+        let position = self.first_positional(&args);
+        match args.get(position) {
+            Some(arg) if *arg != self.flag_long_start => {
+                let external_args = args.split_off(position);
+                (args, Some(external_args))
+            }
+            _ => (args, None),
+        }
+    }
+
+    /// Returns the position of the first positional or `--` in args after the program name,
+    /// skipping flags and their values, or the length of args when there is none
+    ///
+    /// # Arguments
+    ///
+    /// `args` - A slice of string that holds arguments, including the program name
+    fn first_positional(&self, args: &[String]) -> usize {
+        let mut position = 1;
+        while let Some(arg) = args.get(position) {
+            if *arg == self.flag_long_start || !self.is_flag(arg) {
+                break;
+            }
+            position += if self.takes_next(arg, args.get(position + 1)) {
+                2
+            } else {
+                1
+            };
+        }
+
+        position.min(args.len())
+    }
+
+    /// Returns if a flag arg takes the following arg as its value
+    ///
+    /// # Arguments
+    ///
+    /// `arg` - A string slice that holds the flag arg
+    ///
+    /// `next` - The following arg if any
+    fn takes_next(&self, arg: &str, next: Option<&String>) -> bool {
+        self.short_cluster_takes_value(arg)
+            || self.all_flags().any(|x| {
+                self.is_match(x, arg)
+                    && (takes_value(x)
+                        || ((x.bool_valued || x.optional_value)
+                            && next.is_some_and(|x| !self.is_flag(x))))
+            })
+    }
+
+    /// Expand `@path` args to the tokens of the files when response files are enabled
+    ///
+    /// # Arguments
+    ///
+    /// `args` - A vector of string that holds arguments, including the program name
+    ///
+    /// # Return
+    ///
+    /// Expanded args, or an error message when a file can not be read
+    fn expand_response_files(&self, args: Vec<String>) -> Result<Vec<String>, String> {
+        if !self.response_files {
+            return Ok(args);
+        }
+
+        let mut expanded: Vec<String> = vec![];
+        let mut args = args.into_iter();
+        expanded.extend(args.next());
+        while let Some(arg) = args.next() {
+            if arg == self.flag_long_start {
+                expanded.push(arg);
+                expanded.extend(args);
+                break;
+            }
+            if expanded.len() > 1
+                && self.is_flag(&expanded[expanded.len() - 1])
+                && self.takes_next(&expanded[expanded.len() - 1], Some(&arg))
+            {
+                expanded.push(arg);
+                continue;
+            }
+            match arg.strip_prefix('@') {
+                Some(rest) if rest.starts_with('@') => expanded.push(String::from(rest)),
+                Some(path) if !path.is_empty() => {
+                    let text = fs::read_to_string(path)
+                        .map_err(|err| format!("failed reading response file {}: {}", path, err))?;
+                    expanded.extend(
+                        split_tokens(&text).ok_or_else(|| {
+                            format!("unterminated quote in response file {}", path)
+                        })?,
+                    );
+                }
+                _ => expanded.push(arg),
+            }
+        }
+
+        Ok(expanded)
+    }
+
+    /// Update flags value by the config loader then config files in order, which environment variables and args take precedence over
+    ///
+    /// Help, version and inherited global flags never fall back
+    ///
+    /// # Return
+    ///
+    /// An error message when a config file fails loading or a config value has a mismatched type
+    fn update_config_flags(&mut self) -> Result<(), String> {
+        let mut values = match &self.config_loader {
+            Some(loader) => loader.load()?,
+            None => vec![],
+        };
+        for loader in self.config_files.iter() {
+            values.extend(loader.load()?);
+        }
+
+        for (key, value) in values {
+            let flag = self
+                .flags
+                .iter_mut()
+                .find(|x| !is_reserved(x) && !x.long.is_empty() && x.long == key);
+            if let Some(flag) = flag {
+                flag.value = config_flag_value(&flag.value, value).map_err(|expected| {
+                    format!(
+                        "invalid value for config key {}: expected {}",
+                        key, expected
+                    )
+                })?;
+                flag.source = ValueSource::Config;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Update flags value by environment variables, which args take precedence over
+    ///
+    /// A flag falls back to the environment variable set by `Flag::env`, or the one derived from the env prefix and its long identifier,
+    /// help, version and inherited global flags never fall back
+    ///
+    /// # Return
+    ///
+    /// An error message when a value of environment variable is invalid
+    fn update_env_flags(&mut self) -> Result<(), String> {
+        let env_prefix = self.env_prefix.clone();
+
+        for flag in self.flags.iter_mut().filter(|x| !is_reserved(x)) {
+            let name = match env_name(flag, env_prefix.as_deref()) {
+                Some(name) => name,
+                None => continue,
+            };
+            let raw = match env::var(&name) {
+                Ok(raw) => raw,
+                Err(_) => continue,
+            };
+            let separator = match flag.value {
+                FlagValue::StringList(_) => flag.list_separator.or(flag.delimiter),
+                _ => None,
+            };
+            flag.value = match separator {
+                Some(separator) => FlagValue::StringList(Some(split_list(&raw, separator))),
+                None => flag_value(&flag.value, &raw).map_err(|err| {
+                    format!(
+                        "invalid value \"{}\" for environment variable {}: {}",
+                        raw, name, err
+                    )
+                })?,
+            };
+            flag.source = ValueSource::Env;
+        }
+
+        Ok(())
+    }
+
+    /// Update flags value by giving args and returns simple args vector without any flag
+    ///
+    /// A flag which takes a value consumes the following arg, or the rest of a short flag like `-nworld`,
+    /// short flags can be bundled like `-abc` or `-xvf file` with a value flag last, a bool valued flag consumes the following arg unless it is a flag
+    ///
+    /// A string value like `@path` is read from the file at `path`, `@@` is escaped as a literal `@`
+    ///
+    /// A trailing flag or the `--` separator stops parsing, every following arg is captured into the trailing flag,
+    /// or kept apart from simple args to be passed to `run` as `Args::trailing` when the command holds no trailing flag
+    ///
+    /// # Arguments
+    ///
+    /// `args` - A vector of string slice that holds arguments
+    ///
+    /// # Return
+    ///
+    /// A vector of string that without any flag from args, or an error message when a flag is unknown or its value is invalid
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let args = vec!["target/debug/hello", "-f", "world"];
+    /// let args = self.set_flags(args);
+    /// dbg!(&args);
+    /// // output:
+    /// // [src/command.rs:95] &args = [
+    /// //     "target/debug/hello",
+    /// //     "world",
+    /// // ]
+    /// ```
+    fn update_flags(&mut self, args: Vec<&str>) -> Result<Vec<String>, String> {
+        let mut simple_args: Vec<String> = vec![];
+        self.help_short = false;
+        self.trailing = vec![];
+
+        for (i, parsed_arg) in self.parse(args).into_iter().enumerate() {
+            if self.trace {
+                let text = self.describe(i, &parsed_arg);
+                for line in text.lines() {
+                    writeln!(self.error_writer, "trace: {}", line)
+                        .expect("failed writing to error writer");
+                }
+            }
+            match parsed_arg {
+                ParsedArg::Simple(arg) => simple_args.push(arg),
+                ParsedArg::Flag(arg, index, value) => {
+                    let flag = self.all_flags().nth(index).unwrap();
+                    let short_help = is_help(flag) && !arg.starts_with(&self.flag_long_start);
+                    if let Some(message) = flag.deprecated.clone() {
+                        self.eprint_warning(&format!("{} is deprecated: {}", arg, message));
+                    }
+                    if short_help {
+                        self.help_short = value == FlagValue::Bool(true);
+                    }
+                    let flag = self.all_flags_mut().nth(index).unwrap();
+                    flag.value = match (&flag.value, value) {
+                        (FlagValue::Int(count), FlagValue::Int(Some(n))) if flag.count => {
+                            FlagValue::Int(Some(count.unwrap_or(0) + n))
+                        }
+                        (_, value) => value,
+                    };
+                    flag.source = ValueSource::Args;
+                }
+                ParsedArg::Trailing(rest) => self.trailing = rest,
+                ParsedArg::Unknown(arg) => return Err(self.unknown_flag_message(&arg)),
+                ParsedArg::Invalid(_, message) => return Err(message),
+            }
+        }
+
+        Ok(simple_args)
+    }
+
+    /// Parse args into simple args, matched flags with their resolved values and unknown flags without updating any flag
+    ///
+    /// # Arguments
+    ///
+    /// `args` - A vector of string slice that holds arguments
+    ///
+    /// # Return
+    ///
+    /// A vector of `ParsedArg` in the order of args
+    fn parse(&self, args: Vec<&str>) -> Vec<ParsedArg> {
+        let mut parsed_args: Vec<ParsedArg> = vec![];
+        let flags: Vec<&Flag> = self.all_flags().collect();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            if arg == self.flag_long_start {
+                let rest: Vec<String> = args.map(String::from).collect();
+                match flags.iter().position(|x| x.trailing) {
+                    Some(index) => parsed_args.push(ParsedArg::Flag(
+                        String::from(arg),
+                        index,
+                        FlagValue::StringList(Some(rest)),
+                    )),
+                    None => parsed_args.push(ParsedArg::Trailing(rest)),
+                }
+                break;
+            }
+            if !(self.is_flag(arg)) {
+                parsed_args.push(ParsedArg::Simple(String::from(arg)));
+                continue;
+            }
+            if let Some(parsed_arg) = self
+                .parse_attached_value(&flags, arg)
+                .or_else(|| self.parse_negated(&flags, arg))
+            {
+                parsed_args.push(parsed_arg);
+                continue;
+            }
+            match flags.iter().position(|x| self.is_match(x, arg)) {
+                Some(index) if flags[index].trailing => {
+                    let rest: Vec<String> = args.map(String::from).collect();
+                    parsed_args.push(ParsedArg::Flag(
+                        String::from(arg),
+                        index,
+                        FlagValue::StringList(Some(rest)),
+                    ));
+                    break;
+                }
+                Some(index) if flags[index].bool_valued || flags[index].optional_value => {
+                    match args.as_slice().first() {
+                        Some(value) if !self.is_flag(value) => {
+                            parsed_args.push(parse_value(arg, index, flags[index], value));
+                            args.next();
+                        }
+                        _ => parsed_args.push(ParsedArg::Flag(
+                            String::from(arg),
+                            index,
+                            bare_value(flags[index]),
+                        )),
+                    }
+                }
+                Some(index) if takes_value(flags[index]) => match args.next() {
+                    Some(value) => parsed_args.push(parse_value(arg, index, flags[index], value)),
+                    None => parsed_args.push(ParsedArg::Invalid(
+                        String::from(arg),
+                        format!("flag {} requires a value", arg),
+                    )),
+                },
+                Some(index) => parsed_args.push(ParsedArg::Flag(
+                    String::from(arg),
+                    index,
+                    used_value(flags[index]),
+                )),
+                None => parsed_args.extend(self.parse_short_cluster(&flags, arg, &mut args)),
+            }
+        }
+
+        parsed_args
+    }
+
+    /// Returns lines which describe how an arg is parsed, as the program name, a matched flag with its resolved value,
+    /// a positional, a trailing arg or an unknown flag
+    ///
+    /// # Arguments
+    ///
+    /// `i` - The position of the parsed arg, `0` for the program name
+    ///
+    /// `parsed_arg` - The parsed arg
+    fn describe(&self, i: usize, parsed_arg: &ParsedArg) -> String {
+        match parsed_arg {
+            ParsedArg::Simple(arg) if i == 0 => format!("{}: program\n", arg),
+            ParsedArg::Simple(arg) => format!("{}: positional\n", arg),
+            ParsedArg::Flag(arg, index, value) => format!(
+                "{}: flag {} = {:?}\n",
+                arg,
+                self.all_flags()
+                    .nth(*index)
+                    .unwrap()
+                    .display_name(&self.flag_short_start, &self.flag_long_start),
+                value
+            ),
+            ParsedArg::Trailing(rest) => rest
+                .iter()
+                .map(|arg| format!("{}: trailing\n", arg))
+                .collect(),
+            ParsedArg::Unknown(arg) => format!("{}: unknown\n", arg),
+            ParsedArg::Invalid(arg, message) => format!("{}: invalid, {}\n", arg, message),
+        }
+    }
+
+    /// Returns the error message of an unknown flag, with a suggestion of the closest long identifier if any
+    ///
+    /// # Arguments
+    ///
+    /// `arg` - A string slice that holds the unknown flag
+    fn unknown_flag_message(&self, arg: &str) -> String {
+        let suggestion = arg
+            .strip_prefix(&self.flag_long_start)
+            .and_then(|name| closest(name, self.all_flags().map(|x| &x.long[..])));
+
+        match suggestion {
+            Some(long) => format!(
+                "unknown flag {}, did you mean {}{}?",
+                arg, self.flag_long_start, long
+            ),
+            None => format!("unknown flag {}", arg),
+        }
+    }
+
+    /// Split positional args like `key=value` into pairs when `kv_positionals` is set
+    ///
+    /// # Arguments
+    ///
+    /// `args` - A vector of string that holds positional args
+    ///
+    /// # Return
+    ///
+    /// Positional args without `=` and pairs split on the first `=`, an arg with an empty key like `=value` is kept
+    fn split_pairs(&self, args: Vec<String>) -> (Vec<String>, Vec<(String, String)>) {
+        if !self.kv_positionals {
+            return (args, vec![]);
+        }
+        let mut values = vec![];
+        let mut pairs = vec![];
+        for arg in args {
+            match arg.split_once('=') {
+                Some((key, value)) if !key.is_empty() => {
+                    pairs.push((String::from(key), String::from(value)))
+                }
+                _ => values.push(arg),
+            }
+        }
+
+        (values, pairs)
+    }
+
+    /// Replace the `-` positional arg by the text read from the input when `dash_stdin` is set
+    ///
+    /// # Arguments
+    ///
+    /// `args` - A vector of string that holds positional args
+    ///
+    /// # Return
+    ///
+    /// Positional args with `-` replaced, or an error message when the input can not be read or `-` is repeated
+    fn read_dash_stdin(&mut self, mut args: Vec<String>) -> Result<Vec<String>, String> {
+        if !self.dash_stdin {
+            return Ok(args);
+        }
+
+        let mut dashes = args.iter_mut().filter(|x| *x == "-");
+        if let Some(arg) = dashes.next() {
+            if dashes.next().is_some() {
+                return Err(String::from("stdin can only be read by one - argument"));
+            }
+            let mut text = String::new();
+            self.input
+                .read_to_string(&mut text)
+                .map_err(|err| format!("failed reading stdin: {}", err))?;
+            *arg = String::from(text.trim());
+        }
+
+        Ok(args)
+    }
+
+    /// Check if the count of positional args satisfies the arity constraint
+    ///
+    /// # Arguments
+    ///
+    /// `args` - A slice of string that holds positional args
+    ///
+    /// # Return
+    ///
+    /// An error message like `expected 2 arguments, got 1` when the constraint is not satisfied
+    fn check_arity(&self, args: &[String]) -> Result<(), String> {
+        let count = args.len();
+        let expected = match self.arity {
+            Some(Arity::Exact(n)) if count != n => plural(n),
+            Some(Arity::Min(n)) if count < n => format!("at least {}", plural(n)),
+            Some(Arity::Range(lo, hi)) if count < lo || count > hi => {
+                format!("{} to {}", lo, plural(hi))
+            }
+            _ => return Ok(()),
+        };
+
+        Err(format!("expected {}, got {}", expected, count))
+    }
+
+    /// Convert positional args into values of declared types
+    ///
+    /// # Arguments
+    ///
+    /// `args` - A vector of string that holds positional args
+    ///
+    /// # Return
+    ///
+    /// Values of positional args, or an error message like `argument a: invalid integer "x"`
+    fn positional_values(&self, args: Vec<String>) -> Result<Vec<FlagValue>, String> {
+        let mut values = vec![];
+
+        for (index, arg) in args.into_iter().enumerate() {
+            let positional = match self.positionals.get(index) {
+                None if self.variadic => self.positionals.last(),
+                positional => positional,
+            };
+            let value = match positional {
+                Some((name, kind)) => flag_value(kind, &arg).map_err(|_| {
+                    format!("argument {}: invalid {} \"{}\"", name, type_name(kind), arg)
+                })?,
+                None => FlagValue::String(Some(arg)),
+            };
+            values.push(value);
+        }
+
+        Ok(values)
+    }
+
+    /// Prompt for and read the values of required flags which are not set when `interactive` is set
+    /// and stdin is a terminal
+    ///
+    /// # Return
+    ///
+    /// `Ok(())`, or an error message when the input can not be read or a value is invalid
+    fn prompt_required_flags(&mut self) -> Result<(), String> {
+        if !(self.interactive && self.input_is_stdin && (self.stdin_is_terminal)()) {
+            return Ok(());
+        }
+
+        let missing: Vec<usize> = self
+            .all_flags()
+            .enumerate()
+            .filter(|(_, x)| x.required && !x.is_provided())
+            .map(|(index, _)| index)
+            .collect();
+        for index in missing {
+            let flag = self.all_flags().nth(index).unwrap();
+            let name = flag.display_name(&self.flag_short_start, &self.flag_long_start);
+            let kind = flag.value.clone();
+            write!(self.error_writer, "Enter value for {}: ", name)
+                .and_then(|_| self.error_writer.flush())
+                .expect("failed writing to error writer");
+
+            let line = read_line(&mut self.input)
+                .map_err(|err| format!("failed reading stdin: {}", err))?;
+            if line.is_empty() {
+                continue;
+            }
+            let value = flag_value(&kind, &line)
+                .map_err(|err| format!("invalid value \"{}\" for flag {}: {}", line, name, err))?;
+            let flag = self.all_flags_mut().nth(index).unwrap();
+            flag.value = value;
+            flag.source = ValueSource::Args;
+        }
+
+        Ok(())
+    }
+
+    /// Substitute `{long}` tokens in the defaults of unset string flags by the values of other flags after parsing
+    ///
+    /// A token naming no flag is left as is, a token naming an unset flag is replaced by an empty string
+    ///
+    /// # Return
+    ///
+    /// An error message like `default of flag --output references itself` when defaults reference each other in a cycle
+    fn interpolate_defaults(&mut self) -> Result<(), String> {
+        let flags: Vec<&Flag> = self.all_flags().collect();
+        let mut resolved = vec![None; flags.len()];
+        for index in 0..flags.len() {
+            if is_template(flags[index]) {
+                if let Err(index) = interpolate(&flags, index, &mut resolved, &mut vec![]) {
+                    return Err(format!(
+                        "default of flag {} references itself",
+                        flags[index].display_name(&self.flag_short_start, &self.flag_long_start)
+                    ));
+                }
+            }
+        }
+
+        for (flag, value) in self.all_flags_mut().zip(resolved) {
+            if let Some(value) = value {
+                flag.value = FlagValue::String(Some(value));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check if flags provided by args, environment variables or config files conflict with or require other flags,
+    /// default values neither conflict nor satisfy
+    ///
+    /// # Return
+    ///
+    /// An error message like `flag --quiet conflicts with --verbose` or `flag --password requires --user`
+    fn check_flag_relations(&self) -> Result<(), String> {
+        let flags: Vec<&Flag> = self.all_flags().collect();
+        let name = |flag: &Flag| flag.display_name(&self.flag_short_start, &self.flag_long_start);
+
+        for flag in flags.iter().filter(|x| x.is_provided()) {
+            for other in flag.conflicts.iter() {
+                if let Some(other) = flags.iter().find(|x| x.is_named(other) && x.is_provided()) {
+                    return Err(format!(
+                        "flag {} conflicts with {}",
+                        name(flag),
+                        name(other)
+                    ));
+                }
+            }
+            for other in flag.requires.iter() {
+                if let Some(other) = flags.iter().find(|x| x.is_named(other) && !x.is_provided()) {
+                    return Err(format!("flag {} requires {}", name(flag), name(other)));
+                }
+            }
+        }
+        if let Some(flag) = flags.iter().find(|x| x.required && !x.is_provided()) {
+            return Err(format!("flag {} is required", name(flag)));
+        }
+        for group in self.required_groups.iter() {
+            let group: Vec<&&Flag> = group
+                .iter()
+                .filter_map(|x| flags.iter().find(|y| y.is_named(x)))
+                .collect();
+            if !group.iter().any(|x| x.is_provided()) {
+                let names: Vec<String> = group.iter().map(|x| name(x)).collect();
+                return Err(format!("one of {} is required", names.join(", ")));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the error message of an unknown subcommand when the first positional is close to a subcommand name
+    ///
+    /// A positional which is not close to any subcommand name is not treated as a subcommand,
+    /// hidden subcommands are never suggested
+    ///
+    /// # Arguments
+    ///
+    /// `args` - A slice of string that holds simple args, including the program name
+    fn unknown_subcommand_message(&self, args: &[String]) -> Option<String> {
+        let arg = args.get(1)?;
+        let name = closest(
+            arg,
+            self.subcommands
+                .iter()
+                .filter(|(_, x)| !x.is_hidden())
+                .map(|(name, _)| &name[..]),
+        )?;
+
+        Some(format!(
+            "unknown command \"{}\", did you mean \"{}\"?",
+            arg, name
+        ))
+    }
+
+    /// Parse a negated long flag, e.g. `--no-color` for a negatable `color` flag
+    ///
+    /// # Arguments
+    ///
+    /// `flags` - A slice of flags which the identifier is matched to
+    ///
+    /// `arg` - A string slice that holds the argument
+    ///
+    /// # Return
+    ///
+    /// The parsed arg with `false` value, or `None` when the arg does not negate any negatable flag
+    /// or matches a flag literally, e.g. a `no-color` flag
+    fn parse_negated(&self, flags: &[&Flag], arg: &str) -> Option<ParsedArg> {
+        if flags.iter().any(|x| self.is_match(x, arg)) {
+            return None;
+        }
+        let long = arg
+            .strip_prefix(&self.flag_long_start)
+            .and_then(|x| x.strip_prefix("no-"))?;
+        let name = format!("{}{}", self.flag_long_start, long);
+        let index = flags
+            .iter()
+            .position(|x| x.negatable && self.is_match(x, &name))?;
+
+        Some(ParsedArg::Flag(
+            String::from(arg),
+            index,
+            FlagValue::Bool(false),
+        ))
+    }
+
+    /// Parse a long flag with a value attached by `=`, e.g. `--color=always`
+    ///
+    /// A bool flag takes a bool spelling like `--verbose=no`, or an empty value like `--verbose=` which sets it,
+    /// the built-in help and version flags take no value
+    ///
+    /// # Arguments
+    ///
+    /// `flags` - A slice of flags which the identifier is matched to
+    ///
+    /// `arg` - A string slice that holds the argument
+    ///
+    /// # Return
+    ///
+    /// The parsed arg, or `None` when the arg has no `=` or the identifier does not match any flag
+    fn parse_attached_value(&self, flags: &[&Flag], arg: &str) -> Option<ParsedArg> {
+        let (name, value) = arg
+            .strip_prefix(&self.flag_long_start)
+            .and_then(|x| x.split_once('='))?;
+        let name = &arg[..self.flag_long_start.len() + name.len()];
+        let index = flags.iter().position(|x| self.is_match(x, name))?;
+        let flag = flags[index];
+
+        let is_bool = matches!(flag.value, FlagValue::Bool(_)) && !flag.reserved;
+        if flag.trailing || !(is_bool || takes_value(flag)) {
+            return Some(ParsedArg::Invalid(
+                String::from(name),
+                format!("flag {} does not take a value", name),
+            ));
+        }
+        if is_bool && value.is_empty() {
+            return Some(ParsedArg::Flag(
+                String::from(name),
+                index,
+                FlagValue::Bool(true),
+            ));
+        }
+        Some(parse_value(name, index, flag, value))
+    }
+
+    /// Returns the indexes of flags in a short flag cluster like `-xvf`,
+    /// when every character is a short bool or count flag or a short flag which takes a value
+    ///
+    /// # Arguments
+    ///
+    /// `flags` - A slice of `Flag` that holds all flags of the command
+    ///
+    /// `arg` - A string slice that holds the arg
+    fn short_cluster_indexes(&self, flags: &[&Flag], arg: &str) -> Option<Vec<usize>> {
+        let cluster = arg
+            .strip_prefix(&self.flag_short_start)
+            .filter(|x| !x.is_empty() && !arg.starts_with(&self.flag_long_start))?;
+
+        cluster
+            .chars()
+            .map(|c| {
+                flags.iter().position(|x| {
+                    x.short == c.to_string()
+                        && (matches!(x.value, FlagValue::Bool(_)) || x.count || takes_value(x))
+                })
+            })
+            .collect()
+    }
+
+    /// Returns if the arg is a short flag cluster whose last flag takes the following arg as its value
+    ///
+    /// # Arguments
+    ///
+    /// `arg` - A string slice that holds the arg
+    fn short_cluster_takes_value(&self, arg: &str) -> bool {
+        let flags: Vec<&Flag> = self.all_flags().collect();
+        self.short_cluster_indexes(&flags, arg)
+            .and_then(|indexes| indexes.last().copied())
+            .is_some_and(|x| takes_value(flags[x]))
+    }
+
+    /// Parse a short flag cluster which matches no flag
+    ///
+    /// The cluster is parsed as bundled flags like `-xvf file` when every character is a short bool or count flag
+    /// or a short flag which takes a value, only the last flag can take a value and it takes the following arg,
+    /// otherwise as a short flag with an attached value like `-nworld` or `-j4` when the first character is a short flag which takes a value
+    ///
+    /// # Arguments
+    ///
+    /// `flags` - A slice of `Flag` that holds all flags of the command
+    ///
+    /// `arg` - A string slice that holds the arg
+    ///
+    /// `args` - An iterator of the following args, which the value of the last flag is taken from
+    fn parse_short_cluster(
+        &self,
+        flags: &[&Flag],
+        arg: &str,
+        args: &mut std::vec::IntoIter<&str>,
+    ) -> Vec<ParsedArg> {
+        if let Some(indexes) = self.short_cluster_indexes(flags, arg) {
+            let mut parsed_args = vec![];
+            for (i, index) in indexes.iter().copied().enumerate() {
+                let flag = flags[index];
+                if !takes_value(flag) {
+                    parsed_args.push(ParsedArg::Flag(String::from(arg), index, used_value(flag)));
+                    continue;
+                }
+                let name = format!("{}{}", self.flag_short_start, flag.short);
+                let parsed_arg = if i + 1 < indexes.len() {
+                    ParsedArg::Invalid(
+                        String::from(arg),
+                        format!("flag {} must be last in {} to take a value", name, arg),
+                    )
+                } else {
+                    match args.next() {
+                        Some(value) => parse_value(&name, index, flag, value),
+                        None => ParsedArg::Invalid(
+                            String::from(arg),
+                            format!("flag {} requires a value", name),
+                        ),
+                    }
+                };
+                parsed_args.push(parsed_arg);
+                break;
+            }
+            return parsed_args;
+        }
+
+        let cluster = match arg.strip_prefix(&self.flag_short_start) {
+            Some(cluster) if !cluster.is_empty() && !arg.starts_with(&self.flag_long_start) => {
+                cluster
+            }
+            _ => return vec![ParsedArg::Unknown(String::from(arg))],
+        };
+        let mut chars = cluster.chars();
+        match chars
+            .next()
+            .and_then(|c| flags.iter().position(|x| x.short == c.to_string()))
+        {
+            Some(index) if takes_value(flags[index]) => {
+                vec![parse_value(arg, index, flags[index], chars.as_str())]
+            }
+            _ => vec![ParsedArg::Unknown(String::from(arg))],
+        }
+    }
+
+    /// Returns added flags and inherited global flags without help or version
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// // returns empty flag vector when not providing any flag
+    /// let mut command = Command::new("gives a friendly hello", "hello TEXT", |args, flags, _| {
+    ///     todo!();
+    /// });
+    /// let flags = command.get_flags();
+    /// assert_eq!(0, flags.len());
+    ///
+    /// // returns non empty flag vector when providing flag
+    /// let mut command = Command::new("gives a friendly hello", "hello TEXT", |args, flags, _| {
+    ///     todo!();
+    /// });
+    /// command.add_flag("f", "ferris", "say hello from ferris");
+    /// let flags = command.get_flags();
+    /// assert_eq!(1, flags.len());
+    /// ```
+    fn get_flags(&self) -> FlagSet<'_> {
+        let mut simple_flags: Vec<&Flag> = vec![];
+
+        for flag in self.all_flags() {
+            if !is_reserved(flag) {
+                simple_flags.push(flag);
+            }
+        }
+
+        FlagSet::new(simple_flags)
+    }
+
+    /// Parse args and handle everything before the logic of the command, e.g. help, version and subcommands
+    ///
+    /// # Arguments
+    ///
+    /// `args` - A vector of string that holds arguments, including the program name
+    ///
+    /// `once` - Whether parsing for `run_once`, which skips environment variables, prints nothing,
+    /// leaves help, version and completion to the logic and runs subcommands by `run_once`
+    ///
+    /// # Return
+    ///
+    /// `Args` to call the logic with, `None` when execution is already finished, or the error printed unless `once`
+    fn prepare_run(&mut self, args: Vec<String>, once: bool) -> Result<Option<Args>, CommandError> {
+        self.help_shown = false;
+        if !self.name_explicit && !args.is_empty() {
+            self.set_name(&program_name(&args));
+        }
+        self.reset_flags();
+        let args = self
+            .expand_response_files(args)
+            .map_err(|message| self.parse_error(message, true, once))?;
+        if !once && self.completion_command && args.get(1).is_some_and(|x| x == COMPLETION_COMMAND)
+        {
+            return self.completion_exit(&args[2..]).map(|_| None);
+        }
+        let (args, subcommand) = self.split_subcommand(args);
+        let (args, external) = match subcommand {
+            Some(_) => (args, None),
+            None => self.split_external(args),
+        };
+        let mut result = self.update_config_flags();
+        if !once {
+            result = result.and_then(|_| self.update_env_flags());
+        }
+        result.map_err(|message| self.parse_error(message, false, once))?;
+        let mut args = self
+            .update_flags(args.iter().map(|x| &x[..]).collect())
+            .map_err(|message| self.parse_error(message, true, once))?;
+        self.interpolate_defaults()
+            .map_err(|message| self.parse_error(message, false, once))?;
+
+        if !once && self.help_version_exit() {
+            return Ok(None);
+        }
+        let subcommand = match subcommand {
+            Some((index, args)) if !once && self.subcommands[index].1.asks_help(&args) => {
+                return self.execute_subcommand(index, args, once).map(|_| None);
+            }
+            subcommand => subcommand,
+        };
+        self.prompt_required_flags()
+            .map_err(|message| self.parse_error(message, false, once))?;
+        self.check_flag_relations()
+            .map_err(|message| self.parse_error(message, true, once))?;
+
+        if let Some((index, args)) = subcommand {
+            return self.execute_subcommand(index, args, once).map(|_| None);
+        }
+        if let (Some(handler), Some(args)) = (&self.external_subcommand, external) {
+            handler(&args[0], &args[1..]);
+            return Ok(None);
+        }
+        if let Some(message) = self.unknown_subcommand_message(&args) {
+            return Err(self.parse_error(message, true, once));
+        }
+
+        if self.stdin_fallback
+            && args.len() <= 1
+            && self.trailing.is_empty()
+            && !(self.input_is_stdin && (self.stdin_is_terminal)())
+        {
+            let mut text = String::new();
+            if let Err(err) = self.input.read_to_string(&mut text) {
+                let message = format!("failed reading stdin: {}", err);
+                return Err(self.parse_error(message, false, once));
+            }
+            args.push(String::from(text.trim()));
+        }
+        if !once
+            && self.help_render.is_some()
+            && self.arity.is_none()
+            && args.len() <= 1
+            && self.trailing.is_empty()
+        {
+            let text = self.help_string();
+            self.print_help(&text);
+            self.help_shown = true;
+            return Ok(None);
+        }
+
+        let args: Vec<String> = args.into_iter().skip(1).collect();
+        let args = self
+            .read_dash_stdin(args)
+            .map_err(|message| self.parse_error(message, false, once))?;
+        let (args, pairs) = self.split_pairs(args);
+        let values = self
+            .check_arity(&args)
+            .and_then(|_| self.positional_values(args))
+            .map_err(|message| self.parse_error(message, true, once))?;
+
+        Ok(Some(Args {
+            values,
+            trailing: std::mem::take(&mut self.trailing),
+            pairs,
+        }))
+    }
+
+    /// Returns a parse error, printed first unless parsing for `run_once`
+    ///
+    /// # Arguments
+    ///
+    /// `message` - A string that holds the error message
+    ///
+    /// `usage` - Whether the error is followed by the short usage footer
+    ///
+    /// `once` - Whether parsing for `run_once`
+    fn parse_error(&mut self, message: String, usage: bool, once: bool) -> CommandError {
+        match (once, usage) {
+            (true, _) => {}
+            (false, true) => self.eprint_usage_error(&message),
+            (false, false) => self.eprint_error(&message),
+        }
+
+        CommandError::Parse(message)
+    }
+
+    /// Call parsed hooks and the logic of the command with positional args, flags and the writer, then flush the writer
+    ///
+    /// # Arguments
+    ///
+    /// `args` - An `Args` that holds positional args converted to declared types and args after `--`
+    fn call_run(&mut self, args: Args) -> Result<(), Box<dyn Error>> {
+        let flags = self.get_flags();
+        for hook in self.parsed_hooks.iter() {
+            hook(&flags);
+        }
+        let mut writer = std::mem::replace(&mut self.writer, Box::new(io::sink()));
+        let result = (self.run)(args, &self.get_flags(), &mut writer)
+            .and_then(|_| writer.flush().map_err(Into::into));
+        self.writer = writer;
+        result
+    }
+
+    /// Check if help or version needed to display and exit,
+    ///
+    /// by if arguments contains `-h`, `--help`, `--help-all`, `-v` or `--version`, help always wins over version so that
+    /// exactly one of them is printed, `-h` renders compact help, `--help` renders full help and `--help-all`
+    /// renders full help including hidden flags
+    fn help_version_exit(&mut self) -> bool {
+        let is_set = |command: &Command, reserved: fn(&Flag) -> bool| {
+            command
+                .flags
+                .iter()
+                .any(|x| reserved(x) && x.value == FlagValue::Bool(true))
+        };
+        let help_all = is_set(self, is_help_all);
+
+        if (is_set(self, is_help) || help_all) && self.help_render.is_some() {
+            self.help_all = help_all;
+            let text = match (&self.compact_help_render, self.help_short && !help_all) {
+                (Some(render), true) => render.help_text(self),
+                _ => self.help_string(),
+            };
+            self.help_all = false;
+            self.print_help(&text);
+            self.help_shown = true;
+            true
+        } else if let (true, Some(render)) = (is_set(self, is_version), &self.version_render) {
+            let text = render.version_text(self);
+            self.println(&text);
+            self.help_shown = true;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Print the completion script of the shell named by the first arg of the `completion` subcommand
+    ///
+    /// # Arguments
+    ///
+    /// `args` - A slice of string that holds args after `completion`
+    fn completion_exit(&mut self, args: &[String]) -> Result<(), CommandError> {
+        let shell = match args {
+            [shell] => shell.parse::<Shell>(),
+            _ => Err(String::from(
+                "expected exactly 1 shell, one of bash, zsh, fish",
+            )),
+        };
+        match shell {
+            Ok(shell) => {
+                let text = self.completion(shell);
+                write!(self.writer, "{}", text).expect("failed writing to writer");
+                Ok(())
+            }
+            Err(message) => {
+                self.eprint_error(&message);
+                Err(CommandError::Parse(message))
+            }
+        }
+    }
+
+    /// Write text followed by a newline to the writer
+    ///
+    /// # Arguments
+    ///
+    /// `text` - A string slice that holds the text to write
+    fn println(&mut self, text: &str) {
+        writeln!(self.writer, "{}", text).expect("failed writing to writer");
+    }
+
+    /// Write help to a pager when paging is enabled and `stdout` is a terminal, otherwise to the writer
+    ///
+    /// # Arguments
+    ///
+    /// `text` - A string slice that holds the help text
+    fn print_help(&mut self, text: &str) {
+        if self.paged_help && self.writer_is_stdout && io::stdout().is_terminal() && page(text) {
+            return;
+        }
+        self.println(text);
+    }
+
+    /// Set the command name and the descriptions of help and version flags with the name,
+    /// names of subcommands without explicit names are updated with the name as the path prefix
+    ///
+    /// # Arguments
+    ///
+    /// `name` - A string slice that holds the command name
+    fn set_name(&mut self, name: &str) {
+        self.name = String::from(name);
+        for (subcommand_name, subcommand) in self.subcommands.iter_mut() {
+            if !subcommand.name_explicit {
+                subcommand.set_name(&format!("{} {}", name, subcommand_name));
+            }
+        }
+        for flag in self.flags.iter_mut() {
+            if is_help(flag) {
+                flag.description = format!("help for {}", name);
+            }
+            if is_version(flag) {
+                flag.description = format!("version for {}", name);
+            }
+            if is_help_all(flag) {
+                flag.description = format!("help for {} including hidden flags", name);
+            }
+        }
+    }
+
+    /// Set the prefixes of flag identifiers, prefixes of subcommands without explicit prefixes are updated as well
+    ///
+    /// # Arguments
+    ///
+    /// `short` - A string slice that holds the prefix of short identifier
+    ///
+    /// `long` - A string slice that holds the prefix of long identifier
+    fn set_flag_prefixes(&mut self, short: &str, long: &str) {
+        self.flag_short_start = String::from(short);
+        self.flag_long_start = String::from(long);
+        for (_, subcommand) in self.subcommands.iter_mut() {
+            if !subcommand.flag_prefixes_explicit {
+                subcommand.set_flag_prefixes(short, long);
+            }
+        }
+    }
+
+    /// Write a warning prefixed with `warning:` to the error writer
+    ///
+    /// # Arguments
+    ///
+    /// `msg` - A string slice that holds the warning message
+    fn eprint_warning(&mut self, msg: &str) {
+        writeln!(self.error_writer, "warning: {}", msg).expect("failed writing to error writer");
+    }
+
+    /// Print a parse error followed by the short usage footer to the error writer
+    ///
+    /// # Arguments
+    ///
+    /// `msg` - A string slice that holds the error message
+    fn eprint_usage_error(&mut self, msg: &str) {
+        self.eprint_error(msg);
+        if self.error_render.is_some() {
+            return;
+        }
+        let text = if self.help_on_error {
+            self.help_string()
+        } else if self.print_usage_on_error {
+            self.short_usage()
+        } else {
+            return;
+        };
+        writeln!(self.error_writer, "{}", text.trim_end()).expect("failed writing to error writer");
+    }
+
+    /// Write an error prefixed with the command name to the error writer
+    ///
+    /// # Arguments
+    ///
+    /// `msg` - A string slice that holds the error message
+    fn eprint_error(&mut self, msg: &str) {
+        self.eprint_command_error(&CommandError::Parse(String::from(msg)));
+    }
+
+    /// Write an error rendered by the error render, or prefixed with the command name, to the error writer
+    ///
+    /// # Arguments
+    ///
+    /// `err` - The error to write
+    fn eprint_command_error(&mut self, err: &CommandError) {
+        if let Some(render) = &self.error_render {
+            let text = render(err);
+            writeln!(self.error_writer, "{}", text).expect("failed writing to error writer");
+            return;
+        }
+        let msg = err.to_string();
+        let color = match self.error_color {
+            Some(color) => color,
+            None => use_color(
+                self.error_writer_is_stderr && io::stderr().is_terminal(),
+                false,
+            ),
+        };
+        let text = paint(&format!("{}: {}", self.name, msg), &self.theme.error, color);
+        writeln!(self.error_writer, "{}", text).expect("failed writing to error writer");
+    }
+}
+
+/// Read a line without the line ending, byte by byte so that nothing after the line is consumed
+fn read_line(input: &mut dyn Read) -> io::Result<String> {
+    let mut bytes = vec![];
+    let mut byte = [0];
+
+    while input.read(&mut byte)? == 1 && byte[0] != b'\n' {
+        bytes.push(byte[0]);
+    }
+    if bytes.last() == Some(&b'\r') {
+        bytes.pop();
+    }
+
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Returns the candidate closest to the input within the suggestion distance
+///
+/// # Arguments
+///
+/// `input` - A string slice that holds the input
+///
+/// `candidates` - An iterator of string slice that holds the candidates
+fn closest<'a>(input: &str, candidates: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    candidates
+        .filter(|x| !x.is_empty())
+        .map(|x| (levenshtein(input, x), x))
+        .filter(|(distance, _)| *distance <= SUGGESTION_DISTANCE)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, x)| x)
+}
+
+/// Pipe text into the pager of the `PAGER` environment variable, `less -R` by default
+///
+/// # Return
+///
+/// Whether the text was paged, `false` when no pager is available
+fn page(text: &str) -> bool {
+    let pager = env::var("PAGER").unwrap_or_else(|_| String::from(DEFAULT_PAGER));
+    let mut parts = pager.split_whitespace();
+    let program = match parts.next() {
+        Some(program) => program,
+        None => return false,
+    };
+    let mut child = match Process::new(program)
+        .args(parts)
+        .stdin(Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(_) => return false,
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        // the pager may quit before reading everything
+        let _ = writeln!(stdin, "{}", text);
+    }
+
+    child.wait().is_ok()
+}
+
+/// Returns the count of arguments with a plural noun, e.g. `1 argument` or `2 arguments`
+fn plural(n: usize) -> String {
+    if n == 1 {
+        String::from("1 argument")
+    } else {
+        format!("{} arguments", n)
+    }
+}
+
+/// Returns the value of a flag with an optional value when used bare, `true` for a bool flag and `""` otherwise
+fn bare_value(flag: &Flag) -> FlagValue {
+    match flag.value {
+        FlagValue::Bool(_) => FlagValue::Bool(true),
+        _ => FlagValue::String(Some(String::new())),
+    }
+}
+
+/// Returns the environment variable name which the flag falls back to, derived from the prefix when not set
+///
+/// # Arguments
+///
+/// `flag` - The flag
+///
+/// `prefix` - The prefix of environment variables set by `env_prefix` if any
+fn env_name(flag: &Flag, prefix: Option<&str>) -> Option<String> {
+    match (&flag.env, prefix) {
+        (Some(name), _) => Some(name.clone()),
+        (None, Some(prefix)) if !flag.long.is_empty() && !is_reserved(flag) => Some(format!(
+            "{}_{}",
+            prefix,
+            flag.long.to_uppercase().replace('-', "_")
+        )),
+        _ => None,
+    }
+}
+
+/// Returns if the text is a number like `5` or `1.5`, digits optionally followed by a fraction
+fn is_number(text: &str) -> bool {
+    let (whole, fraction) = match text.split_once('.') {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (text, None),
+    };
+    let is_digits = |x: &str| !x.is_empty() && x.chars().all(|c| c.is_ascii_digit());
+
+    is_digits(whole) && fraction.is_none_or(is_digits)
+}
+
+/// Returns the value of a flag which takes no value when used, `true` for a bool flag and an increment of `1` for a count flag
+fn used_value(flag: &Flag) -> FlagValue {
+    if flag.count {
+        FlagValue::Int(Some(1))
+    } else {
+        FlagValue::Bool(true)
+    }
+}
+
+/// Returns if the flag takes a value from args
+fn takes_value(flag: &Flag) -> bool {
+    match flag.value {
+        FlagValue::String(_) | FlagValue::Float(_) => true,
+        FlagValue::Int(_) => !flag.count,
+        FlagValue::StringList(_) => flag.delimiter.is_some(),
+        FlagValue::Bool(_) => false,
+    }
+}
+
+/// Split a raw value on the delimiter, empty segments are dropped and a backslash escapes the delimiter
+///
+/// # Arguments
+///
+/// `raw` - A string slice that holds the raw value, e.g. `a,b\,c`
+///
+/// `delimiter` - The delimiter, e.g. `,`
+fn split_list(raw: &str, delimiter: char) -> Vec<String> {
+    let mut values = vec![];
+    let mut value = String::new();
+    let mut chars = raw.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == '\\' && chars.peek() == Some(&delimiter) {
+            value.push(delimiter);
+            chars.next();
+        } else if c == delimiter {
+            values.push(std::mem::take(&mut value));
+        } else {
+            value.push(c);
+        }
+    }
+    values.push(value);
+
+    values.into_iter().filter(|x| !x.is_empty()).collect()
+}
+
+/// Split text on whitespace, quotes keep `"a b"` or `'a b'` as one token, `None` when a quote is unterminated
+fn split_tokens(text: &str) -> Option<Vec<String>> {
+    let mut tokens: Vec<String> = vec![];
+    let mut token: Option<String> = None;
+    let mut quote: Option<char> = None;
+
+    for c in text.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => token.get_or_insert_with(String::new).push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                token.get_or_insert_with(String::new);
+            }
+            (None, c) if c.is_whitespace() => tokens.extend(token.take()),
+            (None, c) => token.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if quote.is_some() {
+        return None;
+    }
+    tokens.extend(token);
+
+    Some(tokens)
+}
+
+/// Returns if the flag is a string flag left at a default which holds a `{long}` token
+fn is_template(flag: &Flag) -> bool {
+    match &flag.value {
+        FlagValue::String(Some(text)) => flag.value == flag.default && text.contains('{'),
+        _ => false,
+    }
+}
+
+/// Returns the default of the template flag at index with `{long}` tokens substituted, templates referenced are resolved first
+///
+/// # Arguments
+///
+/// `flags` - A slice of flags which tokens are looked up in
+///
+/// `index` - The index of the template flag
+///
+/// `resolved` - Substituted defaults by index, which are filled as templates are resolved
+///
+/// `stack` - Indexes of templates being resolved, for detecting cycles
+///
+/// # Return
+///
+/// The substituted default, or the index of a template which references itself in a cycle
+fn interpolate(
+    flags: &[&Flag],
+    index: usize,
+    resolved: &mut Vec<Option<String>>,
+    stack: &mut Vec<usize>,
+) -> Result<String, usize> {
+    if let Some(text) = &resolved[index] {
+        return Ok(text.clone());
+    }
+    if stack.contains(&index) {
+        return Err(index);
+    }
+    stack.push(index);
+
+    let template = flags[index].value.to_string();
+    let mut text = String::new();
+    let mut rest = &template[..];
+    while let Some(start) = rest.find('{') {
+        text.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let other = rest.find('}').and_then(|end| {
+            let position = flags
+                .iter()
+                .position(|x| !x.long.is_empty() && x.long == rest[1..end])?;
+            Some((end, position))
+        });
+        match other {
+            Some((end, other)) => {
+                let value = if is_template(flags[other]) {
+                    interpolate(flags, other, resolved, stack)?
+                } else {
+                    flags[other].value.to_string()
+                };
+                text.push_str(&value);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                text.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    text.push_str(rest);
+
+    stack.pop();
+    resolved[index] = Some(text.clone());
+    Ok(text)
+}
+
+/// Returns a value as JSON, `null` when the value is unset
+fn json_value(value: &FlagValue) -> String {
+    match value {
+        FlagValue::Bool(value) => value.to_string(),
+        FlagValue::String(Some(value)) => json_string(value),
+        FlagValue::Int(Some(value)) => value.to_string(),
+        FlagValue::Float(Some(value)) if value.is_finite() => value.to_string(),
+        FlagValue::StringList(Some(values)) => {
+            let values: Vec<String> = values.iter().map(|x| json_string(x)).collect();
+            format!("[{}]", values.join(","))
+        }
+        _ => String::from("null"),
+    }
+}
+
+/// Returns if the flag is the built-in help, help all or version flag, flags added by the user are never reserved
+fn is_reserved(flag: &Flag) -> bool {
+    flag.reserved
+}
+
+/// Returns if the flag is the built-in help flag
+fn is_help(flag: &Flag) -> bool {
+    flag.reserved && flag.long == HELP_LONG
+}
+
+/// Returns if the flag is the help all flag added by `enable_help_all`
+fn is_help_all(flag: &Flag) -> bool {
+    flag.reserved && flag.long == HELP_ALL_LONG
+}
+
+/// Returns if the flag is the built-in version flag
+fn is_version(flag: &Flag) -> bool {
+    flag.reserved && flag.long == VERSION_LONG
+}
+
+/// Returns the name of the type of a value used in error messages
+fn type_name(kind: &FlagValue) -> &'static str {
+    match kind {
+        FlagValue::Bool(_) => "bool",
+        FlagValue::String(_) => "string",
+        FlagValue::Int(_) => "integer",
+        FlagValue::Float(_) => "float",
+        FlagValue::StringList(_) => "string list",
+    }
+}
+
+/// Convert a raw value into a value of the same type as the kind
+///
+/// # Arguments
+///
+/// `kind` - A `FlagValue` whose variant is the type which the value is converted to
+///
+/// `raw` - A string slice that holds the raw value
+///
+/// # Return
+///
+/// The converted value, or an error message like `expected an integer` when `raw` is invalid
+fn flag_value(kind: &FlagValue, raw: &str) -> Result<FlagValue, String> {
+    let mut value = kind.clone();
+    value.parse_into(raw)?;
+    Ok(value)
+}
+
+/// Convert a config value into a value of the same type as the kind
+///
+/// # Arguments
+///
+/// `kind` - A `FlagValue` whose variant is the type which the value is converted to
+///
+/// `value` - A `ConfigValue` to convert
+///
+/// # Return
+///
+/// The converted value, or the description of the expected value when `value` has a mismatched type
+fn config_flag_value(kind: &FlagValue, value: ConfigValue) -> Result<FlagValue, &'static str> {
+    match (kind, value) {
+        (FlagValue::Bool(_), ConfigValue::Bool(x)) => Ok(FlagValue::Bool(x)),
+        (FlagValue::Bool(_), ConfigValue::String(x)) => {
+            parse_bool(&x).map(FlagValue::Bool).ok_or("a bool")
+        }
+        (FlagValue::Bool(_), _) => Err("a bool"),
+        (FlagValue::String(_), ConfigValue::String(x)) => Ok(FlagValue::String(Some(x))),
+        (FlagValue::String(_), _) => Err("a string"),
+        (FlagValue::Int(_), ConfigValue::Number(x))
+            if x.fract() == 0.0 && x >= i32::MIN as f64 && x <= i32::MAX as f64 =>
+        {
+            Ok(FlagValue::Int(Some(x as i32)))
+        }
+        (FlagValue::Int(_), _) => Err("an integer"),
+        (FlagValue::Float(_), ConfigValue::Number(x)) => Ok(FlagValue::Float(Some(x as f32))),
+        (FlagValue::Float(_), _) => Err("a float"),
+        (FlagValue::StringList(_), ConfigValue::String(x)) => {
+            Ok(FlagValue::StringList(Some(vec![x])))
+        }
+        (FlagValue::StringList(_), _) => Err("a string"),
+    }
+}
+
+/// Parse a raw value from args into the value of the flag
+///
+/// # Arguments
+///
+/// `arg` - A string slice that holds the arg which matches the flag
+///
+/// `index` - The index of the flag
+///
+/// `flag` - A `Flag` which takes a value
+///
+/// `raw` - A string slice that holds the raw value, a string value like `@path` is read from the file at `path`
+/// with its contents trimmed, and `@@` is escaped as a literal `@`
+fn parse_value(arg: &str, index: usize, flag: &Flag, raw: &str) -> ParsedArg {
+    let raw = match (&flag.value, raw.strip_prefix('@')) {
+        (FlagValue::String(_), Some(rest)) if rest.starts_with('@') => String::from(rest),
+        (FlagValue::String(_), Some(path)) => match fs::read_to_string(path) {
+            Ok(contents) => String::from(contents.trim()),
+            Err(err) => {
+                return ParsedArg::Invalid(
+                    String::from(arg),
+                    format!(
+                        "failed reading value for flag {} from file {}: {}",
+                        arg, path, err
+                    ),
+                )
+            }
+        },
+        _ => String::from(raw),
+    };
+
+    if let (FlagValue::StringList(_), Some(delimiter)) = (&flag.value, flag.delimiter) {
+        let values = split_list(&raw, delimiter);
+        return ParsedArg::Flag(
+            String::from(arg),
+            index,
+            FlagValue::StringList(Some(values)),
+        );
+    }
+
+    match flag_value(&flag.value, &raw) {
+        Ok(value) => ParsedArg::Flag(String::from(arg), index, value),
+        Err(err) => ParsedArg::Invalid(
+            String::from(arg),
+            format!("invalid value \"{}\" for flag {}: {}", raw, arg, err),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::RefCell,
+        io::Cursor,
+        rc::Rc,
+        sync::atomic::{AtomicI32, Ordering},
+    };
+
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct Buffer(Rc<RefCell<Vec<u8>>>);
+
+    impl Buffer {
+        fn text(&self) -> String {
+            String::from_utf8(self.0.borrow().clone()).unwrap()
+        }
+    }
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|x| String::from(*x)).collect()
+    }
+
+    #[test]
+    fn try_execute_ok() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        assert_eq!(command.try_execute(args(&["test", "text"])), Ok(()));
+    }
+
+    #[test]
+    fn try_execute_err() {
+        let buffer = Buffer::default();
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| {
+            Err("test error".into())
+        });
+        command.error_writer(Box::new(buffer.clone()));
+        let result = command.try_execute(args(&["test", "text"]));
+        assert!(matches!(result, Err(code) if code != 0));
+        assert_eq!(buffer.text(), "test: test error\n");
+    }
+
+    #[test]
+    fn try_execute_minimal() {
+        let buffer = Buffer::default();
+        let mut command = Command::minimal("test description", "test TEXT", |args, _, out| {
+            writeln!(out, "{} args", args.len())?;
+            Ok(())
+        });
+        command.writer(Box::new(buffer.clone()));
+        command.error_writer(Box::new(io::sink()));
+        assert_eq!(command.flag_count(true), 0);
+        assert_eq!(command.help_string(), "");
+        assert_eq!(command.try_execute(args(&["test", "-h"])), Err(1));
+        assert_eq!(command.try_execute(args(&["test"])), Ok(()));
+        assert_eq!(buffer.text(), "0 args\n");
+
+        command.add_flag(Flag::new_bool("h", "help", "test help"));
+        assert_eq!(command.try_execute(args(&["test", "-h"])), Ok(()));
+        assert_eq!(buffer.text(), "0 args\n0 args\n");
+
+        let mut command = Command::minimal("test description", "test TEXT", |_, flags, _| {
+            match flags.get_str("host") {
+                Some("x") => Ok(()),
+                _ => Err("unexpected host".into()),
+            }
+        });
+        command.error_writer(Box::new(io::sink()));
+        command.add_flag(Flag::new_string("h", "host", "test string"));
+        assert_eq!(command.flag_count(false), 1);
+        assert_eq!(command.try_execute(args(&["test", "-h", "x"])), Ok(()));
+        assert!(command.flags_json().contains("\"host\""));
+    }
+
+    #[test]
+    fn try_execute_name() {
+        let buffer = Buffer::default();
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.writer(Box::new(buffer.clone()));
+        assert_eq!(
+            command.try_execute(args(&["/usr/bin/hello", "--help"])),
+            Ok(())
+        );
+        assert_eq!(command.name, "hello");
+        assert!(buffer.text().contains("help for hello\n"));
+        assert!(!buffer.text().contains(PKG_NAME));
+
+        let buffer = Buffer::default();
+        let mut subcommand = Command::new("sub description", "", |_, _, _| Ok(()));
+        subcommand.error_writer(Box::new(buffer.clone()));
+        let mut command =
+            Command::new("test description", "test COMMAND", |_, _, _| Ok(())).name("configured");
+        command.add_subcommand("sub", subcommand);
+        assert_eq!(
+            command.try_execute(args(&["hello", "sub", "--foo"])),
+            Err(1)
+        );
+        assert_eq!(command.name, "configured");
+        assert_eq!(
+            buffer.text(),
+            "configured sub: unknown flag --foo\nUsage: configured sub\nTry 'configured sub --help' for more information.\n"
+        );
+    }
+
+    #[test]
+    fn try_execute_error_color() {
+        let buffer = Buffer::default();
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.error_writer(Box::new(buffer.clone()));
+        command.error_color(true);
+        assert_eq!(command.try_execute(args(&["test", "--foo"])), Err(1));
+        assert!(buffer
+            .text()
+            .starts_with("\x1b[31mtest: unknown flag --foo\x1b[0m\nUsage: test TEXT\n"));
+
+        let buffer = Buffer::default();
+        command.error_writer(Box::new(buffer.clone()));
+        command.error_color(false);
+        assert_eq!(command.try_execute(args(&["test", "--foo"])), Err(1));
+        assert!(!buffer.text().contains('\x1b'));
+    }
+
+    #[test]
+    fn try_execute_error_render() {
+        let buffer = Buffer::default();
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.error_writer(Box::new(buffer.clone()));
+        command.error_render(Box::new(|err| {
+            let kind = match err {
+                CommandError::Parse(_) => "parse",
+                CommandError::Run(_) => "run",
+            };
+            format!("{{\"kind\":\"{}\",\"message\":\"{}\"}}", kind, err)
+        }));
+        assert_eq!(command.try_execute(args(&["test", "--foo"])), Err(1));
+        assert_eq!(
+            buffer.text(),
+            "{\"kind\":\"parse\",\"message\":\"unknown flag --foo\"}\n"
+        );
+    }
+
+    #[test]
+    fn validate() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.add_flag(Flag::new_bool("q", "quiet", "test bool").conflicts_with("verbose"));
+        command.add_flag(Flag::new_bool("V", "verbose", "test bool"));
+        command.add_flag(Flag::new_string("p", "password", "test string").requires("u"));
+        assert_eq!(
+            command.validate(),
+            Err(String::from("flag --password requires unknown flag u"))
+        );
+
+        command.add_flag(Flag::new_string("u", "user", "test string"));
+        assert_eq!(command.validate(), Ok(()));
+
+        command.add_flag(Flag::new_bool("x", "quiet", "test bool"));
+        assert_eq!(
+            command.validate(),
+            Err(String::from("duplicate flag --quiet"))
+        );
+    }
+
+    #[test]
+    fn try_execute_require_one_of() {
+        let buffer = Buffer::default();
+        let mut command = Command::new("test description", "test", |_, _, _| Ok(()));
+        command.add_flag(Flag::new_string("i", "input", "test string"));
+        command.add_flag(Flag::new_bool("s", "stdin", "test bool"));
+        command.require_one_of(&["input", "s"]);
+        command.args_exact(0);
+        command.error_writer(Box::new(buffer.clone()));
+        assert_eq!(command.validate(), Ok(()));
+
+        assert_eq!(command.try_execute(args(&["test"])), Err(1));
+        assert!(buffer
+            .text()
+            .starts_with("test: one of --input, --stdin is required\n"));
+        assert_eq!(command.try_execute(args(&["test", "-i", "file"])), Ok(()));
+        assert_eq!(command.try_execute(args(&["test", "--stdin"])), Ok(()));
+
+        command.require_one_of(&["input", "file"]);
+        assert_eq!(
+            command.validate(),
+            Err(String::from("required group references unknown flag file"))
+        );
+    }
+
+    #[test]
+    fn validate_examples() {
+        let mut sub = Command::new("sub description", "TEXT", |_, _, _| Ok(()));
+        sub.add_flag(Flag::new_bool("x", "xray", "test bool"));
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.add_flag(Flag::new_string("n", "name", "test string"));
+        command.add_flag(Flag::new_bool("c", "color", "test negatable").negatable());
+        command.add_flag(Flag::new_bool("q", "quiet", "test global").global());
+        command.add_subcommand("sub", sub);
+        command.add_example("test --name=world --no-color text", "test example");
+        command.add_example("test sub --quiet --xray text -- --other", "test example");
+        assert_eq!(command.validate(), Ok(()));
+
+        command.add_example("test --nmae world", "test typo");
+        assert_eq!(
+            command.validate(),
+            Err(String::from(
+                "example \"test --nmae world\" references unknown flag --nmae"
+            ))
+        );
+    }
+
+    #[test]
+    fn validate_conflicts_unknown_flag() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.add_flag(Flag::new_bool("q", "quiet", "test bool").conflicts_with("loud"));
+        assert_eq!(
+            command.validate(),
+            Err(String::from(
+                "flag --quiet conflicts with unknown flag loud"
+            ))
+        );
+    }
+
+    #[test]
+    fn run_once_flag_relations() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.add_flag(Flag::new_bool("q", "quiet", "test bool").conflicts_with("verbose"));
+        command.add_flag(Flag::new_bool("V", "verbose", "test bool"));
+        command.add_flag(Flag::new_string("p", "password", "test string").requires("user"));
+        command.add_flag(Flag::new_string("u", "user", "test string"));
+        assert!(matches!(
+            command.run_once(args(&["test", "-q", "-V"])),
+            Err(CommandError::Parse(message)) if message == "flag --quiet conflicts with --verbose"
+        ));
+        assert!(matches!(
+            command.run_once(args(&["test", "-p", "secret"])),
+            Err(CommandError::Parse(message)) if message == "flag --password requires --user"
+        ));
+        assert!(command
+            .run_once(args(&["test", "-p", "secret", "-u", "ferris", "-q"]))
+            .is_ok());
+
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.add_flag(Flag::new_bool("q", "quiet", "test bool").conflicts_with("level"));
+        command.add_flag(
+            Flag::new_int("l", "level", "test int").default_value(FlagValue::Int(Some(1))),
+        );
+        command.add_flag(Flag::new_string("p", "password", "test string").requires("user"));
+        command.add_flag(
+            Flag::new_string("u", "user", "test string")
+                .default_value(FlagValue::String(Some(String::from("root")))),
+        );
+        command.add_flag(
+            Flag::new_string("n", "name", "test string")
+                .default_value(FlagValue::String(Some(String::from("ferris"))))
+                .required(),
+        );
+        assert!(command.run_once(args(&["test", "-q", "-n", "x"])).is_ok());
+        assert!(matches!(
+            command.run_once(args(&["test", "-q", "-l", "2", "-n", "x"])),
+            Err(CommandError::Parse(message)) if message == "flag --quiet conflicts with --level"
+        ));
+        assert!(matches!(
+            command.run_once(args(&["test", "-p", "secret", "-n", "x"])),
+            Err(CommandError::Parse(message)) if message == "flag --password requires --user"
+        ));
+        assert!(matches!(
+            command.run_once(args(&["test"])),
+            Err(CommandError::Parse(message)) if message == "flag --name is required"
+        ));
+    }
+
+    #[test]
+    fn try_execute_unknown_flag() {
+        let buffer = Buffer::default();
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.error_writer(Box::new(buffer.clone()));
+        let result = command.try_execute(args(&["test", "--foo", "text"]));
+        assert_eq!(result, Err(1));
+        assert_eq!(
+            buffer.text(),
+            "test: unknown flag --foo\nUsage: test TEXT\nTry 'test --help' for more information.\n"
+        );
+
+        let buffer = Buffer::default();
+        command.error_writer(Box::new(buffer.clone()));
+        command.help_hint(false);
+        assert_eq!(command.try_execute(args(&["test", "--foo"])), Err(1));
+        assert_eq!(
+            buffer.text(),
+            "test: unknown flag --foo\nUsage: test TEXT\n"
+        );
+
+        let buffer = Buffer::default();
+        command.error_writer(Box::new(buffer.clone()));
+        command.print_usage_on_error(false);
+        assert_eq!(command.try_execute(args(&["test", "--foo"])), Err(1));
+        assert_eq!(buffer.text(), "test: unknown flag --foo\n");
+    }
+
+    #[test]
+    fn try_execute_help_on_error() {
+        let buffer = Buffer::default();
+        let mut command = Command::new("test description", "test", |_, _, _| Ok(()));
+        command.add_flag(Flag::new_string("n", "name", "test string").required());
+        command.args_exact(0);
+        command.error_writer(Box::new(buffer.clone()));
+        command.help_on_error(true);
+        assert_eq!(command.try_execute(args(&["test"])), Err(1));
+        assert_eq!(
+            buffer.text(),
+            format!(
+                "test: flag --name is required\n{}\n",
+                command.help_string().trim_end()
+            )
+        );
+        assert!(buffer.text().contains("--name"));
+        assert!(!buffer.text().contains("Try 'test --help'"));
+    }
+
+    #[test]
+    fn execute_with_exit_codes() {
+        static EXIT_CODE: AtomicI32 = AtomicI32::new(-1);
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.add_subcommand(
+            "sub",
+            Command::new("sub description", "TEXT", |_, _, _| Ok(())),
+        );
+        command.writer(Box::new(io::sink()));
+        command.error_writer(Box::new(io::sink()));
+        command.exit = |code| EXIT_CODE.store(code, Ordering::SeqCst);
+
+        command.execute_with(args(&["test", "--help"]));
+        assert_eq!(EXIT_CODE.load(Ordering::SeqCst), -1);
+        command.execute_with(args(&["test", "--foo"]));
+        assert_eq!(EXIT_CODE.load(Ordering::SeqCst), 1);
+
+        command.help_exit_code(3);
+        command.error_exit_code(2);
+        command.execute_with(args(&["test", "--version"]));
+        assert_eq!(EXIT_CODE.load(Ordering::SeqCst), 3);
+        command.execute_with(args(&["test", "--foo"]));
+        assert_eq!(EXIT_CODE.load(Ordering::SeqCst), 2);
+        EXIT_CODE.store(-1, Ordering::SeqCst);
+        command.execute_with(args(&["test", "text"]));
+        assert_eq!(EXIT_CODE.load(Ordering::SeqCst), -1);
+        command.execute_with(args(&["test", "sub", "--help"]));
+        assert_eq!(EXIT_CODE.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn try_execute_help_all() {
+        let buffer = Buffer::default();
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.add_flag(Flag::new_bool("", "debug", "test hidden").hidden());
+        command.add_flag(
+            Flag::new_bool("", "colour", "test deprecated")
+                .deprecated("use --color")
+                .hidden(),
+        );
+        command.writer(Box::new(buffer.clone()));
+        assert_eq!(command.try_execute(args(&["test", "--help-all"])), Err(1));
+
+        command.enable_help_all();
+        assert!(command.get_flags().iter().all(|x| x.long != "help-all"));
+        assert_eq!(command.try_execute(args(&["test", "--help"])), Ok(()));
+        assert!(buffer.text().contains("--help-all"));
+        assert!(!buffer.text().contains("--debug"));
+
+        let buffer = Buffer::default();
+        command.writer(Box::new(buffer.clone()));
+        assert_eq!(command.try_execute(args(&["test", "--help-all"])), Ok(()));
+        assert!(buffer.text().contains("test hidden (hidden)"));
+        assert!(buffer
+            .text()
+            .contains("test deprecated (hidden) (deprecated: use --color)"));
+        assert!(!command.is_help_all());
+
+        let buffer = Buffer::default();
+        command.writer(Box::new(buffer.clone()));
+        command.help_template("{flags}");
+        assert_eq!(command.try_execute(args(&["test", "--help-all"])), Ok(()));
+        assert!(buffer.text().contains("test hidden (hidden)"));
+    }
+
+    #[test]
+    fn writer_help() {
+        let buffer = Buffer::default();
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.writer(Box::new(buffer.clone()));
+        assert_eq!(command.try_execute(args(&["test", "--help"])), Ok(()));
+        let help = command.help_string();
+        assert_eq!(buffer.text(), format!("{}\n", help));
+        assert!(buffer.text().starts_with("test description\n"));
+        assert!(buffer.text().ends_with("\n"));
+        assert!(!buffer.text().ends_with("\n\n"));
+    }
+
+    #[test]
+    fn writer_run() {
+        let buffer = Buffer::default();
+        let mut command = Command::new("test description", "test TEXT", |args, _, out| {
+            for arg in args.iter() {
+                writeln!(out, "hello, {}!", arg)?;
+            }
+            Ok(())
+        });
+        command.writer(Box::new(buffer.clone()));
+        command.args_min(1);
+        assert!(command.try_execute(args(&["test", "x", "y"])).is_ok());
+        assert_eq!(buffer.text(), "hello, x!\nhello, y!\n");
+    }
+
+    #[test]
+    fn writer_help_over_version() {
+        for input in [
+            ["test", "--help", "--version"],
+            ["test", "--version", "--help"],
+        ] {
+            let buffer = Buffer::default();
+            let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+            command.writer(Box::new(buffer.clone()));
+            assert!(command.try_execute(args(&input)).is_ok());
+            assert_eq!(buffer.text(), format!("{}\n", command.help_string()));
+        }
+    }
+
+    #[test]
+    fn try_execute_completion_command() {
+        let buffer = Buffer::default();
+        let error_buffer = Buffer::default();
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| {
+            Err("unexpected run".into())
+        });
+        command.writer(Box::new(buffer.clone()));
+        command.error_writer(Box::new(error_buffer.clone()));
+        command.args_exact(1);
+        command.enable_completion_command();
+        assert_eq!(
+            command.try_execute(args(&["test", "completion", "bash"])),
+            Ok(())
+        );
+        assert_eq!(buffer.text(), command.completion(Shell::Bash));
+        assert!(!command.help_string().contains(COMPLETION_COMMAND));
+
+        assert_eq!(
+            command.try_execute(args(&["test", "completion", "ksh"])),
+            Err(1)
+        );
+        assert_eq!(
+            error_buffer.text(),
+            "test: unknown shell \"ksh\", expected one of bash, zsh, fish\n"
+        );
+    }
+
+    #[test]
+    fn install_completion() {
+        let dir = env::temp_dir().join("cancer_test_install_completion");
+        fs::create_dir_all(&dir).unwrap();
+        let command = Command::new("test description", "test TEXT", |_, _, _| Ok(())).name("test");
+        for (shell, file_name) in [
+            (Shell::Bash, "test.bash"),
+            (Shell::Zsh, "_test"),
+            (Shell::Fish, "test.fish"),
+        ] {
+            let path = command.install_completion(shell, &dir).unwrap();
+            assert_eq!(path, dir.join(file_name));
+            assert_eq!(
+                fs::read_to_string(&path).unwrap(),
+                command.completion(shell)
+            );
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn set_help_render() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        assert!(command.help_string().starts_with("test description\n"));
+
+        command.set_help_render(Box::new(CompactHelpRender::new()));
+        assert_eq!(command.help_string(), "Usage: test TEXT");
+    }
+
+    #[test]
+    fn writer_paged_help() {
+        let buffer = Buffer::default();
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.writer(Box::new(buffer.clone()));
+        command.paged_help();
+        assert_eq!(command.try_execute(args(&["test", "--help"])), Ok(()));
+        assert!(buffer.text().starts_with("test description\n"));
+    }
+
+    #[test]
+    fn writer_compact_help() {
+        let buffer = Buffer::default();
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.writer(Box::new(buffer.clone()));
+        assert_eq!(command.try_execute(args(&["test", "-h"])), Ok(()));
+        assert_eq!(buffer.text(), "Usage: test TEXT\n");
+
+        assert_eq!(command.try_execute(args(&["test", "--help"])), Ok(()));
+        assert!(buffer
+            .text()
+            .contains("Usage: test TEXT\ntest description\n"));
+    }
+
+    #[test]
+    fn update_flags_trailing() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.add_flag(Flag::new_bool("x", "xray", "test bool"));
+        command.add_flag(Flag::new_trailing("a", "args", "test trailing"));
+        let simple_args = command
+            .update_flags(vec!["test", "text", "--args", "-x", "-y", "z"])
+            .unwrap();
+        assert_eq!(simple_args, vec!["test", "text"]);
+        assert_eq!(command.flags[2].value, FlagValue::Bool(false));
+        assert_eq!(
+            command.flags[3].value,
+            FlagValue::StringList(Some(args(&["-x", "-y", "z"])))
+        );
+    }
+
+    #[test]
+    fn update_flags_separator() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        let simple_args = command
+            .update_flags(vec!["test", "--", "-h", "text"])
+            .unwrap();
+        assert_eq!(simple_args, vec!["test"]);
+        assert_eq!(command.trailing, args(&["-h", "text"]));
+        assert_eq!(command.flags[0].value, FlagValue::Bool(false));
+
+        command.add_flag(Flag::new_trailing("a", "args", "test trailing"));
+        let simple_args = command
+            .update_flags(vec!["test", "--", "-h", "text"])
+            .unwrap();
+        assert_eq!(simple_args, vec!["test"]);
+        assert_eq!(
+            command.flags[2].value,
+            FlagValue::StringList(Some(args(&["-h", "text"])))
+        );
+    }
+
+    #[test]
+    fn run_once_on_parsed() {
+        thread_local! {
+            static CALLS: RefCell<Vec<String>> = const { RefCell::new(vec![]) };
+        }
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| {
+            CALLS.with(|x| x.borrow_mut().push(String::from("run")));
+            Ok(())
+        });
+        command.add_flag(Flag::new_bool("V", "verbose", "test bool"));
+        for name in ["first", "second"] {
+            command.on_parsed(Box::new(move |flags| {
+                let verbose = flags.get_bool("verbose") == Some(true);
+                CALLS.with(|x| x.borrow_mut().push(format!("{} {}", name, verbose)));
+            }));
+        }
+        command.run_once(args(&["test", "-V", "text"])).unwrap();
+        assert_eq!(
+            CALLS.with(|x| x.borrow().clone()),
+            vec!["first true", "second true", "run"]
+        );
+    }
+
+    #[test]
+    fn try_execute_subcommand_on_parsed_global_flag() {
+        thread_local! {
+            static FLAGS: RefCell<Vec<String>> = const { RefCell::new(vec![]) };
+        }
+        let mut subcommand = Command::new("sub description", "TEXT", |_, _, _| Ok(()));
+        subcommand.on_parsed(Box::new(|flags| {
+            FLAGS.with(|x| {
+                x.borrow_mut().extend(
+                    flags
+                        .iter()
+                        .map(|flag| format!("{} {}", flag.long, flag.is_set())),
+                )
+            });
+        }));
+        let mut command = Command::new("test description", "test COMMAND", |_, _, _| Ok(()));
+        command.add_flag(Flag::new_bool("V", "verbose", "test bool").global());
+        command.add_subcommand("sub", subcommand);
+        assert_eq!(
+            command.try_execute(args(&["test", "sub", "--verbose", "text"])),
+            Ok(())
+        );
+        assert_eq!(FLAGS.with(|x| x.borrow().clone()), vec!["verbose true"]);
+    }
+
+    #[test]
+    fn run_once_kv_positionals() {
+        let mut command = Command::new("test description", "test KEY=VALUE...", |args, _, _| {
+            let pairs = vec![
+                (String::from("a"), String::from("1")),
+                (String::from("b"), String::from("2")),
+            ];
+            match &args[..] {
+                [FlagValue::String(Some(plain))] if plain == "plain" && args.pairs == pairs => {
+                    Ok(())
+                }
+                _ => Err("unexpected args".into()),
+            }
+        });
+        command.kv_positionals();
+        command.args_variadic();
+        assert!(command
+            .run_once(args(&["test", "a=1", "b=2", "plain"]))
+            .is_ok());
+        assert!(command.run_once(args(&["test", "a=1", "plain"])).is_err());
+    }
+
+    #[test]
+    fn run_once_trailing() {
+        let mut command =
+            Command::new(
+                "test description",
+                "test TEXT -- COMMAND",
+                |args, _, _| match (&args[..], &args.trailing[..]) {
+                    ([FlagValue::String(Some(text))], [program, flag])
+                        if text == "text" && program == "ls" && flag == "-l" =>
+                    {
+                        Ok(())
+                    }
+                    _ => Err("unexpected args".into()),
+                },
+            );
+        assert!(command
+            .run_once(args(&["test", "text", "--", "ls", "-l"]))
+            .is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "only one trailing flag is allowed per command")]
+    fn add_flag_trailing_twice() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.add_flag(Flag::new_trailing("a", "args", "test trailing"));
+        command.add_flag(Flag::new_trailing("r", "rest", "test trailing"));
+    }
+
+    #[test]
+    fn explain() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.add_flag(Flag::new_bool("x", "xray", "test bool"));
+        command.add_flag(Flag::new_short("y", "test short", FlagValue::Bool(false)));
+        let report = command.explain(args(&["test", "--xray", "text", "-y", "--unknown"]));
+        assert_eq!(
+            report,
+            "test: program\n--xray: flag --xray = Bool(true)\ntext: positional\n-y: flag -y = Bool(true)\n--unknown: unknown\n"
+        );
+        assert_eq!(command.flags[2].value, FlagValue::Bool(false));
+    }
+
+    #[test]
+    fn update_flags_trace() {
+        let buffer = Buffer::default();
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.error_writer(Box::new(buffer.clone()));
+        command.add_flag(Flag::new_bool("x", "xray", "test bool"));
+        command.add_flag(Flag::new_int("j", "jobs", "test int"));
+        command.update_flags(vec!["test", "-x"]).unwrap();
+        assert_eq!(buffer.text(), "");
+
+        command.trace(true);
+        command
+            .update_flags(vec!["test", "text", "--jobs", "4", "--", "a"])
+            .unwrap();
+        assert_eq!(
+            buffer.text(),
+            "trace: test: program\ntrace: text: positional\ntrace: --jobs: flag --jobs = Int(Some(4))\ntrace: a: trailing\n"
+        );
+    }
+
+    #[test]
+    fn try_execute_subcommand() {
+        let mut command = Command::new("test description", "test COMMAND", |_, _, _| Ok(()));
+        command.add_subcommand(
+            "sub",
+            Command::new("sub description", "TEXT", |args, _, _| {
+                Err(args[0].to_string().into())
+            }),
+        );
+        assert_eq!(command.try_execute(args(&["test", "sub", "text"])), Err(1));
+        assert_eq!(command.try_execute(args(&["test", "text"])), Ok(()));
+    }
+
+    #[test]
+    fn try_execute_subcommand_writers() {
+        let buffer = Buffer::default();
+        let error_buffer = Buffer::default();
+        let mut command = Command::new("test description", "test COMMAND", |_, _, _| Ok(()));
+        command.add_subcommand(
+            "sub",
+            Command::new("sub description", "TEXT", |args, _, out| {
+                writeln!(out, "{}", args[0])?;
+                Ok(())
+            }),
+        );
+        command.writer(Box::new(buffer.clone()));
+        command.error_writer(Box::new(error_buffer.clone()));
+        command.error_color(true);
+        command.theme(Theme {
+            error: String::from("\x1b[35m"),
+            ..Theme::default()
+        });
+        assert_eq!(command.try_execute(args(&["test", "sub", "text"])), Ok(()));
+        assert_eq!(buffer.text(), "text\n");
+        assert_eq!(command.try_execute(args(&["test", "sub", "--bad"])), Err(1));
+        assert!(error_buffer
+            .text()
+            .starts_with("\x1b[35mtest sub: unknown flag --bad\x1b[0m\n"));
+        assert_eq!(
+            command.try_execute(args(&["test", "sub", "--help"])),
+            Ok(())
+        );
+        assert!(buffer.text().contains("sub description"));
+    }
+
+    #[test]
+    fn dispatch() {
+        let mut command = Command::new("test description", "test TEXT", |args, flags, out| {
+            writeln!(
+                out,
+                "{} {}",
+                args[0],
+                flags.get_bool("xray").unwrap_or(false)
+            )?;
+            Ok(())
+        });
+        command.add_flag(Flag::new_bool("x", "xray", "test bool"));
+        let mut sub = Command::new("sub description", "TEXT", |args, _, _| {
+            Err(args[0].to_string().into())
+        });
+        sub.error_writer(Box::new(io::sink()));
+        command.add_subcommand("sub", sub);
+        let buffer = Buffer::default();
+        command.writer(Box::new(buffer.clone()));
+        command.error_writer(Box::new(io::sink()));
+
+        assert!(command.dispatch(args(&["test", "-x", "one"])).is_ok());
+        assert!(command.dispatch(args(&["test", "two"])).is_ok());
+        assert_eq!(buffer.text(), "one true\ntwo false\n");
+        assert!(matches!(
+            command.dispatch(args(&["test", "--foo", "three"])),
+            Err(CommandError::Parse(message)) if message == "unknown flag --foo"
+        ));
+        assert!(matches!(
+            command.dispatch(args(&["test", "sub", "four"])),
+            Err(CommandError::Run(err)) if err.to_string() == "four"
+        ));
+    }
+
+    #[test]
+    fn try_execute_external_subcommand() {
+        thread_local! {
+            static CALLS: RefCell<Vec<(String, Vec<String>)>> = const { RefCell::new(vec![]) };
+        }
+        let mut command = Command::new("test description", "test COMMAND", |_, _, _| Ok(()));
+        command.add_flag(Flag::new_bool("x", "xray", "test bool"));
+        command.add_subcommand(
+            "status",
+            Command::new("status description", "", |_, _, _| Ok(())),
+        );
+        command.external_subcommand(Box::new(|name, args| {
+            CALLS.with(|x| x.borrow_mut().push((String::from(name), args.to_vec())))
+        }));
+
+        assert_eq!(command.try_execute(args(&["test", "status"])), Ok(()));
+        assert_eq!(
+            command.try_execute(args(&["test", "-x", "foo", "--bar", "baz"])),
+            Ok(())
+        );
+        assert_eq!(command.flags[2].value, FlagValue::Bool(true));
+        CALLS.with(|x| {
+            assert_eq!(
+                *x.borrow(),
+                vec![(
+                    String::from("foo"),
+                    vec![String::from("--bar"), String::from("baz")]
+                )]
+            )
+        });
+    }
+
+    #[test]
+    fn try_execute_subcommand_alias() {
+        let mut command = Command::new("test description", "test COMMAND", |_, _, _| Ok(()));
+        command.add_subcommand(
+            "status",
+            Command::new("status description", "", |_, _, _| Ok(())),
+        );
+        let mut checkout = Command::new("checkout description", "BRANCH", |args, _, _| {
+            Err(format!("checkout {}", args[0]).into())
+        });
+        let buffer = Buffer::default();
+        checkout.error_writer(Box::new(buffer.clone()));
+        checkout.add_alias("co");
+        command.add_subcommand("checkout", checkout);
+        assert_eq!(command.validate(), Ok(()));
+
+        assert_eq!(command.try_execute(args(&["test", "co", "main"])), Err(1));
+        assert_eq!(buffer.text(), "test checkout: checkout main\n");
+
+        let mut status = Command::new("other description", "", |_, _, _| Ok(()));
+        status.add_alias("co");
+        command.add_subcommand("other", status);
+        assert_eq!(
+            command.validate(),
+            Err(String::from("duplicate command co"))
+        );
+    }
+
+    #[test]
+    fn try_execute_subcommand_help() {
+        let buffer = Buffer::default();
+        let parent_buffer = Buffer::default();
+        let mut subcommand = Command::new("sub description", "TEXT", |_, _, _| Ok(()));
+        subcommand.writer(Box::new(buffer.clone()));
+        let mut command = Command::new("test description", "test COMMAND", |_, _, _| Ok(()));
+        command.writer(Box::new(parent_buffer.clone()));
+        command.add_subcommand("sub", subcommand);
+
+        assert_eq!(
+            command.try_execute(args(&["test", "sub", "--help"])),
+            Ok(())
+        );
+        assert!(buffer.text().starts_with("sub description\n"));
+        assert!(!buffer.text().contains("test description"));
+        assert!(parent_buffer.text().is_empty());
+
+        assert_eq!(command.try_execute(args(&["test", "--help"])), Ok(()));
+        assert!(parent_buffer
+            .text()
+            .contains("Commands:\n  sub    sub description\n"));
+    }
+
+    #[test]
+    fn try_execute_subcommand_help_required_parent_flag() {
+        let buffer = Buffer::default();
+        let error_buffer = Buffer::default();
+        let mut subcommand = Command::new("sub description", "TEXT", |_, _, _| Ok(()));
+        subcommand.writer(Box::new(buffer.clone()));
+        let mut command = Command::new("test description", "test COMMAND", |_, _, _| Ok(()));
+        command.error_writer(Box::new(error_buffer.clone()));
+        command.add_flag(Flag::new_string("t", "token", "test required").required());
+        command.add_subcommand("sub", subcommand);
+
+        assert_eq!(
+            command.try_execute(args(&["test", "sub", "--help"])),
+            Ok(())
+        );
+        assert!(buffer.text().starts_with("sub description\n"));
+        assert!(error_buffer.text().is_empty());
+
+        assert_eq!(
+            command.try_execute(args(&["test", "sub", "--", "--help"])),
+            Err(1)
+        );
+        assert!(error_buffer.text().contains("flag --token is required"));
+    }
+
+    #[test]
+    fn try_execute_nested_subcommand_help() {
+        let buffer = Buffer::default();
+        let mut add = Command::new("add description", "URL", |_, _, _| Ok(()));
+        add.writer(Box::new(buffer.clone()));
+        let mut remote = Command::new("remote description", "COMMAND", |_, _, _| Ok(()));
+        remote.add_subcommand("add", add);
+        let mut command = Command::new("test description", "app COMMAND", |_, _, _| Ok(()));
+        command.add_subcommand("remote", remote);
+
+        assert_eq!(
+            command.try_execute(args(&["/bin/app", "remote", "add", "--help"])),
+            Ok(())
+        );
+        assert!(buffer.text().contains("Usage:\n  app remote add URL\n"));
+    }
+
+    #[test]
+    fn try_execute_default_subcommand() {
+        let buffer = Buffer::default();
+        let mut subcommand = Command::new("sub description", "", |args, _, _| {
+            Err(args[0].to_string().into())
+        });
+        subcommand.writer(Box::new(buffer.clone()));
+        let mut command = Command::new("test description", "test COMMAND", |_, _, _| Ok(()));
+        command.writer(Box::new(Buffer::default()));
+        command.add_subcommand("sub", subcommand);
+        command.default_subcommand("sub");
+
+        assert_eq!(command.try_execute(args(&["test"])), Ok(()));
+        assert!(buffer.text().starts_with("sub description\n"));
+        assert_eq!(command.try_execute(args(&["test", "text"])), Err(1));
+    }
+
+    #[test]
+    fn try_execute_subcommand_global_flag() {
+        let buffer = Buffer::default();
+        let mut subcommand = Command::new("sub description", "TEXT", |_, flags, _| {
+            assert_eq!(flags[0].value, FlagValue::Bool(true));
+            Ok(())
+        });
+        subcommand.writer(Box::new(buffer.clone()));
+        let mut command = Command::new("test description", "test COMMAND", |_, _, _| Ok(()));
+        command.add_flag(Flag::new_bool("V", "verbose", "test global").global());
+        command.add_subcommand("sub", subcommand);
+
+        assert_eq!(
+            command.try_execute(args(&["test", "sub", "--verbose", "text"])),
+            Ok(())
+        );
+        assert_eq!(command.flags[2].value, FlagValue::Bool(true));
+
+        assert_eq!(
+            command.try_execute(args(&["test", "sub", "--help"])),
+            Ok(())
+        );
+        assert!(buffer.text().contains("Global Flags:\n  -V, --verbose"));
+        assert!(command.subcommands[0].1.global_flags.is_empty());
+    }
+
+    #[test]
+    fn update_flags_value() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.add_flag(Flag::new_string("n", "name", "test string"));
+        command.add_flag(Flag::new_int("c", "count", "test int"));
+        let simple_args = command
+            .update_flags(vec!["test", "-n", "world", "--count", "3", "text"])
+            .unwrap();
+        assert_eq!(simple_args, vec!["test", "text"]);
+        assert_eq!(
+            command.flags[2].value,
+            FlagValue::String(Some(String::from("world")))
+        );
+        assert_eq!(command.flags[3].value, FlagValue::Int(Some(3)));
+
+        let result = command.update_flags(vec!["test", "--count", "three"]);
+        assert_eq!(
+            result,
+            Err(String::from(
+                "invalid value \"three\" for flag --count: expected an integer"
+            ))
+        );
+        let result = command.update_flags(vec!["test", "--count"]);
+        assert_eq!(result, Err(String::from("flag --count requires a value")));
+    }
+
+    #[test]
+    fn update_flags_attached_value() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.add_flag(Flag::new_string("n", "name", "test string"));
+        command.update_flags(vec!["test", "-nworld"]).unwrap();
+        assert_eq!(
+            command.flags[2].value,
+            FlagValue::String(Some(String::from("world")))
+        );
+    }
+
+    #[test]
+    fn update_flags_attached_int() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.add_flag(Flag::new_bool("a", "all", "test bool"));
+        command.add_flag(Flag::new_int("j", "jobs", "test int"));
+        command.update_flags(vec!["test", "-j4"]).unwrap();
+        assert_eq!(command.flags[3].value, FlagValue::Int(Some(4)));
+
+        let simple_args = command
+            .update_flags(vec!["test", "-j", "4", "text"])
+            .unwrap();
+        assert_eq!(simple_args, vec!["test", "text"]);
+        assert_eq!(command.flags[3].value, FlagValue::Int(Some(4)));
+
+        assert_eq!(
+            command.update_flags(vec!["test", "-ja"]),
+            Err(String::from("flag -j must be last in -ja to take a value"))
+        );
+    }
+
+    #[test]
+    fn update_flags_csv() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.add_flag(Flag::new_string_list_csv("t", "tags", "test list"));
+        let simple_args = command
+            .update_flags(vec!["test", "--tags", "a,,b,c", "text"])
+            .unwrap();
+        assert_eq!(simple_args, vec!["test", "text"]);
+        assert_eq!(
+            command.flags[2].value,
+            FlagValue::StringList(Some(args(&["a", "b", "c"])))
+        );
+
+        command.update_flags(vec!["test", "-ta\\,b,c"]).unwrap();
+        assert_eq!(
+            command.flags[2].value,
+            FlagValue::StringList(Some(args(&["a,b", "c"])))
+        );
+    }
+
+    #[test]
+    fn update_flags_bundled_bools() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.add_flag(Flag::new_bool("a", "all", "test bool"));
+        command.add_flag(Flag::new_bool("b", "brief", "test bool"));
+        command.add_flag(Flag::new_bool("c", "color", "test bool"));
+        command.add_flag(Flag::new_string("n", "name", "test string"));
+        command.update_flags(vec!["test", "-abc", "-nxyz"]).unwrap();
+        assert_eq!(command.flags[2].value, FlagValue::Bool(true));
+        assert_eq!(command.flags[3].value, FlagValue::Bool(true));
+        assert_eq!(command.flags[4].value, FlagValue::Bool(true));
+        assert_eq!(
+            command.flags[5].value,
+            FlagValue::String(Some(String::from("xyz")))
+        );
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn execute_async() {
+        use std::{
+            future::Future,
+            pin::pin,
+            sync::Arc,
+            task::{Context, Poll, Wake, Waker},
+        };
+
+        struct NoopWaker;
+        impl Wake for NoopWaker {
+            fn wake(self: Arc<Self>) {}
+        }
+        fn block_on<F: Future>(future: F) -> F::Output {
+            let waker = Waker::from(Arc::new(NoopWaker));
+            let mut context = Context::from_waker(&waker);
+            let mut future = pin!(future);
+            loop {
+                if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+                    return output;
+                }
             }
         }
 
-        exit
+        let buffer = Buffer::default();
+        let mut command = Command::new_async("test description", "test TEXT", |args, _, out| {
+            Box::pin(async move {
+                match &args[..] {
+                    [FlagValue::String(Some(arg))] => writeln!(out, "hello, {}!", arg)?,
+                    _ => return Err("unexpected args".into()),
+                }
+                Ok(())
+            })
+        });
+        command.writer(Box::new(buffer.clone()));
+        command.error_writer(Box::new(io::sink()));
+        assert_eq!(
+            block_on(command.execute_async(args(&["test", "world"]))),
+            Ok(())
+        );
+        assert_eq!(buffer.text(), "hello, world!\n");
+        assert_eq!(command.try_execute(args(&["test", "world"])), Err(1));
+    }
+
+    #[test]
+    fn update_flags_negative_number() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.add_flag(Flag::new_int("n", "offset", "test int"));
+        let simple_args = command
+            .update_flags(vec!["test", "-5", "-1.5", "-n", "-3"])
+            .unwrap();
+        assert_eq!(simple_args, vec!["test", "-5", "-1.5"]);
+        assert_eq!(command.flags[2].value, FlagValue::Int(Some(-3)));
+
+        command.add_flag(Flag::new_bool("5", "five", "test digit"));
+        let simple_args = command.update_flags(vec!["test", "-5"]).unwrap();
+        assert_eq!(simple_args, vec!["test"]);
+        assert_eq!(command.flags[3].value, FlagValue::Bool(true));
+        assert!(command.update_flags(vec!["test", "-5x"]).is_err());
+    }
+
+    #[test]
+    fn update_flags_bundled_count() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.add_flag(Flag::new_count("d", "debug", "test count"));
+        command.add_flag(Flag::new_bool("q", "quiet", "test bool"));
+        command.update_flags(vec!["test", "-dqd"]).unwrap();
+        assert_eq!(command.flags[2].value, FlagValue::Int(Some(2)));
+        assert_eq!(command.flags[3].value, FlagValue::Bool(true));
+
+        command.reset_flags();
+        command
+            .update_flags(vec!["test", "-dd", "--debug", "-d"])
+            .unwrap();
+        assert_eq!(command.flags[2].value, FlagValue::Int(Some(4)));
+        assert!(command.update_flags(vec!["test", "--debug=2"]).is_err());
+    }
+
+    #[test]
+    fn update_flags_bundled_value() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.add_flag(Flag::new_bool("x", "extract", "test bool"));
+        command.add_flag(Flag::new_bool("V", "verbose", "test bool"));
+        command.add_flag(Flag::new_string("f", "file", "test string"));
+        let simple_args = command
+            .update_flags(vec!["test", "-xVf", "file", "text"])
+            .unwrap();
+        assert_eq!(simple_args, vec!["test", "text"]);
+        assert_eq!(command.flags[2].value, FlagValue::Bool(true));
+        assert_eq!(command.flags[3].value, FlagValue::Bool(true));
+        assert_eq!(
+            command.flags[4].value,
+            FlagValue::String(Some(String::from("file")))
+        );
+
+        assert_eq!(
+            command.update_flags(vec!["test", "-fx", "file"]),
+            Err(String::from("flag -f must be last in -fx to take a value"))
+        );
+    }
+
+    #[test]
+    fn try_execute_subcommand_after_value() {
+        let mut command = Command::new("test description", "test COMMAND", |_, _, _| Ok(()));
+        command.add_flag(Flag::new_string("n", "name", "test string"));
+        command.add_subcommand(
+            "sub",
+            Command::new("sub description", "TEXT", |args, _, _| {
+                Err(args[0].to_string().into())
+            }),
+        );
+        assert_eq!(
+            command.try_execute(args(&["test", "-n", "sub", "sub", "text"])),
+            Err(1)
+        );
+        assert_eq!(
+            command.flags[2].value,
+            FlagValue::String(Some(String::from("sub")))
+        );
+    }
+
+    #[test]
+    fn flag_mut() {
+        let mut command = Command::new("test description", "test TEXT", |_, flags, _| match flags
+            .get_int("port")
+        {
+            Some(9090) => Ok(()),
+            _ => Err("unexpected port".into()),
+        });
+        command.error_writer(Box::new(Buffer::default()));
+        command.add_flag(Flag::new_int("p", "port", "test int"));
+        command.flag_mut("p").unwrap().value = FlagValue::Int(Some(8080));
+        assert_eq!(command.flags[2].value, FlagValue::Int(Some(8080)));
+        assert!(command.flag_mut("missing").is_none());
+
+        command.flag_mut("port").unwrap().default = FlagValue::Int(Some(9090));
+        assert_eq!(command.try_execute(args(&["test", "text"])), Ok(()));
+        assert_eq!(
+            command.try_execute(args(&["test", "-p", "1", "text"])),
+            Err(1)
+        );
+    }
+
+    #[test]
+    fn flag_count() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        assert_eq!(command.flag_count(false), 0);
+        assert_eq!(command.flag_count(true), 2);
+        assert!(command.has_flag("help"));
+        assert!(command.has_flag("v"));
+        assert!(!command.has_flag("xray"));
+
+        command.add_flag(Flag::new_bool("x", "xray", "test bool"));
+        assert_eq!(command.flag_count(false), 1);
+        assert_eq!(command.flag_count(true), 3);
+        assert!(command.has_flag("xray"));
+    }
+
+    #[test]
+    fn flag_value() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.add_flag(Flag::new_int("c", "count", "test int"));
+        assert_eq!(command.flag_value("count"), Some(&FlagValue::Int(None)));
+        assert_eq!(
+            command.try_execute(args(&["test", "--count", "3", "text"])),
+            Ok(())
+        );
+        assert_eq!(command.flag_value("count"), Some(&FlagValue::Int(Some(3))));
+        assert_eq!(command.flag_value("c"), Some(&FlagValue::Int(Some(3))));
+        assert_eq!(command.flag_value("xray"), None);
+    }
+
+    #[test]
+    fn flags_json() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        assert_eq!(command.flags_json(), "[]");
+
+        command.add_flag(Flag::new_string("n", "name", "test \"string\"").required());
+        command.add_flag(
+            Flag::new_float("", "ratio", "test float").default_value(FlagValue::Float(Some(1.5))),
+        );
+        command.add_flag(Flag::new_string_list_csv("t", "tags", "test list"));
+        assert_eq!(
+            command.flags_json(),
+            concat!(
+                r#"[{"short":"n","long":"name","description":"test \"string\"","type":"string","default":null,"required":true},"#,
+                r#"{"short":"","long":"ratio","description":"test float","type":"float","default":1.5,"required":false},"#,
+                r#"{"short":"t","long":"tags","description":"test list","type":"string list","default":null,"required":false}]"#
+            )
+        );
+    }
+
+    #[test]
+    fn try_execute_reset_flags() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.add_flag(Flag::new_string("n", "name", "test string"));
+        command.add_flag(
+            Flag::new_int("c", "count", "test int").default_value(FlagValue::Int(Some(1))),
+        );
+        assert_eq!(
+            command.try_execute(args(&["test", "-n", "world", "-c", "3", "text"])),
+            Ok(())
+        );
+        assert_eq!(
+            command.flags[2].value,
+            FlagValue::String(Some(String::from("world")))
+        );
+        assert_eq!(command.flags[3].value, FlagValue::Int(Some(3)));
+
+        assert_eq!(command.try_execute(args(&["test", "text"])), Ok(()));
+        assert_eq!(command.flags[2].value, FlagValue::String(None));
+        assert_eq!(command.flags[3].value, FlagValue::Int(Some(1)));
+    }
+
+    #[test]
+    fn try_execute_env_prefix() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.env_prefix("CANCER_TEST_PREFIX");
+        command.add_flag(Flag::new_int("m", "max-size", "test int"));
+        command.add_flag(Flag::new_string("n", "name", "test string").env("CANCER_TEST_NAME"));
+        env::set_var("CANCER_TEST_PREFIX_MAX_SIZE", "3");
+        env::set_var("CANCER_TEST_PREFIX_NAME", "prefix");
+        env::set_var("CANCER_TEST_NAME", "explicit");
+
+        assert_eq!(command.try_execute(args(&["test", "text"])), Ok(()));
+        assert_eq!(command.flags[2].value, FlagValue::Int(Some(3)));
+        assert_eq!(
+            command.flags[3].value,
+            FlagValue::String(Some(String::from("explicit")))
+        );
+
+        assert_eq!(
+            command.try_execute(args(&["test", "--max-size", "4", "text"])),
+            Ok(())
+        );
+        assert_eq!(command.flags[2].value, FlagValue::Int(Some(4)));
+    }
+
+    #[test]
+    fn try_execute_interpolate_defaults() {
+        let mut command = Command::new("test description", "test", |_, _, _| Ok(()));
+        command.add_flag(Flag::new_string("n", "name", "test string"));
+        command.add_flag(
+            Flag::new_string("o", "output", "test template")
+                .default_value(FlagValue::String(Some(String::from("{name}.png")))),
+        );
+        command.args_exact(0);
+        assert_eq!(command.try_execute(args(&["test", "-n", "ferris"])), Ok(()));
+        assert_eq!(
+            command.flag_value("output"),
+            Some(&FlagValue::String(Some(String::from("ferris.png"))))
+        );
+
+        assert_eq!(
+            command.try_execute(args(&["test", "-n", "ferris", "-o", "out.png"])),
+            Ok(())
+        );
+        assert_eq!(
+            command.flag_value("output"),
+            Some(&FlagValue::String(Some(String::from("out.png"))))
+        );
+
+        command.add_flag(
+            Flag::new_string("a", "alpha", "test template")
+                .default_value(FlagValue::String(Some(String::from("{beta}")))),
+        );
+        command.add_flag(
+            Flag::new_string("b", "beta", "test template")
+                .default_value(FlagValue::String(Some(String::from("{alpha}")))),
+        );
+        command.error_writer(Box::new(io::sink()));
+        assert!(matches!(
+            command.dispatch(args(&["test"])),
+            Err(CommandError::Parse(message)) if message == "default of flag --alpha references itself"
+        ));
+    }
+
+    #[test]
+    fn try_execute_env_list_separator() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.add_flag(
+            Flag::new_string_list_csv("p", "paths", "test list")
+                .env("CANCER_TEST_PATHS")
+                .list_separator(':'),
+        );
+        env::set_var("CANCER_TEST_PATHS", "/a:/b:/c");
+
+        assert_eq!(command.try_execute(args(&["test", "text"])), Ok(()));
+        assert_eq!(
+            command.flags[2].value,
+            FlagValue::StringList(Some(vec![
+                String::from("/a"),
+                String::from("/b"),
+                String::from("/c")
+            ]))
+        );
+
+        assert_eq!(
+            command.try_execute(args(&["test", "--paths", "/d,/e", "text"])),
+            Ok(())
+        );
+        assert_eq!(
+            command.flags[2].value,
+            FlagValue::StringList(Some(vec![String::from("/d"), String::from("/e")]))
+        );
+    }
+
+    struct TestConfigLoader(Vec<(String, ConfigValue)>);
+
+    impl ConfigLoader for TestConfigLoader {
+        fn load(&self) -> Result<Vec<(String, ConfigValue)>, String> {
+            Ok(self.0.clone())
+        }
+    }
+
+    #[test]
+    fn try_execute_config_loader() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.add_flag(Flag::new_string("n", "name", "test string"));
+        command.add_flag(Flag::new_int("c", "count", "test int"));
+        command.add_flag(Flag::new_bool("q", "quiet", "test bool"));
+        command.config_loader(Box::new(TestConfigLoader(vec![
+            (String::from("name"), ConfigValue::String(String::from("x"))),
+            (String::from("count"), ConfigValue::Number(3.0)),
+            (
+                String::from("quiet"),
+                ConfigValue::String(String::from("on")),
+            ),
+            (String::from("other"), ConfigValue::Bool(true)),
+        ])));
+        assert_eq!(
+            command.try_execute(args(&["test", "-c", "4", "text"])),
+            Ok(())
+        );
+        assert_eq!(
+            command.flags[2].value,
+            FlagValue::String(Some(String::from("x")))
+        );
+        assert_eq!(command.flags[3].value, FlagValue::Int(Some(4)));
+        assert_eq!(command.flags[4].value, FlagValue::Bool(true));
+
+        assert_eq!(command.try_execute(args(&["test", "text"])), Ok(()));
+        assert_eq!(command.flags[3].value, FlagValue::Int(Some(3)));
+    }
+
+    #[test]
+    fn try_execute_config_files() {
+        let global = env::temp_dir().join("cancer_test_config_files_global.json");
+        let local = env::temp_dir().join("cancer_test_config_files_local.json");
+        let missing = env::temp_dir().join("cancer_test_config_files_missing.json");
+        fs::write(&global, r#"{"name": "global", "count": 1}"#).unwrap();
+        fs::write(&local, r#"{"name": "local"}"#).unwrap();
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.add_flag(Flag::new_string("n", "name", "test string"));
+        command.add_flag(Flag::new_int("c", "count", "test int"));
+        command.add_config_file(&global);
+        command.add_config_file(&missing);
+        command.add_config_file(&local);
+
+        assert_eq!(command.try_execute(args(&["test", "text"])), Ok(()));
+        assert_eq!(
+            command.flag_value("name"),
+            Some(&FlagValue::String(Some(String::from("local"))))
+        );
+        assert_eq!(command.flag_value("count"), Some(&FlagValue::Int(Some(1))));
+
+        assert_eq!(
+            command.try_execute(args(&["test", "-n", "arg", "text"])),
+            Ok(())
+        );
+        assert_eq!(
+            command.flag_value("name"),
+            Some(&FlagValue::String(Some(String::from("arg"))))
+        );
+
+        let buffer = Buffer::default();
+        command.error_writer(Box::new(buffer.clone()));
+        command.add_config_file_required(&missing);
+        assert_eq!(command.try_execute(args(&["test", "text"])), Err(1));
+        assert!(buffer.text().starts_with(&format!(
+            "test: failed reading config file {}",
+            missing.display()
+        )));
+    }
+
+    #[test]
+    fn try_execute_config_loader_mismatch() {
+        let buffer = Buffer::default();
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.error_writer(Box::new(buffer.clone()));
+        command.add_flag(Flag::new_int("c", "count", "test int"));
+        command.config_loader(Box::new(TestConfigLoader(vec![(
+            String::from("count"),
+            ConfigValue::String(String::from("three")),
+        )])));
+        assert_eq!(command.try_execute(args(&["test", "text"])), Err(1));
+        assert_eq!(
+            buffer.text(),
+            "test: invalid value for config key count: expected an integer\n"
+        );
+    }
+
+    #[test]
+    fn try_execute_flag_prefixes() {
+        let buffer = Buffer::default();
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.writer(Box::new(buffer.clone()));
+        command.flag_prefixes("/", "//");
+        command.add_flag(Flag::new_string("n", "name", "test string"));
+        command.update_flags(vec!["test", "//name", "-x"]).unwrap();
+        assert_eq!(
+            command.flags[2].value,
+            FlagValue::String(Some(String::from("-x")))
+        );
+
+        assert_eq!(command.try_execute(args(&["test", "//help"])), Ok(()));
+        assert!(buffer.text().contains("  /h, //help       help for"));
+
+        let buffer = Buffer::default();
+        let mut command = Command::new("test description", "test COMMAND", |_, _, _| Ok(()));
+        command.add_subcommand(
+            "sub",
+            Command::new("sub description", "TEXT", |_, _, _| Err("sub ran".into())),
+        );
+        command.writer(Box::new(buffer.clone()));
+        command.flag_prefixes("/", "//");
+        assert_eq!(command.try_execute(args(&["test", "sub", "/h"])), Ok(()));
+        assert_eq!(buffer.text(), "Usage: test sub TEXT\n");
+
+        let mut subcommand = Command::new("sub description", "TEXT", |_, _, _| Ok(()));
+        subcommand.flag_prefixes("+", "++");
+        command.add_subcommand("own", subcommand);
+        assert_eq!(command.subcommands[1].1.flag_long_start, "++");
+    }
+
+    #[test]
+    fn update_flags_unknown_flag_suggestion() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.add_flag(Flag::new_bool("V", "verbose", "test bool"));
+        assert_eq!(
+            command.update_flags(vec!["test", "--verbos"]),
+            Err(String::from(
+                "unknown flag --verbos, did you mean --verbose?"
+            ))
+        );
+        assert_eq!(
+            command.update_flags(vec!["test", "--quiet"]),
+            Err(String::from("unknown flag --quiet"))
+        );
+    }
+
+    #[test]
+    fn try_execute_unknown_subcommand_suggestion() {
+        let buffer = Buffer::default();
+        let mut command = Command::new("test description", "test COMMAND", |_, _, _| Ok(()));
+        command.error_writer(Box::new(buffer.clone()));
+        command.add_subcommand(
+            "status",
+            Command::new("status description", "", |_, _, _| Ok(())),
+        );
+
+        assert_eq!(command.try_execute(args(&["test", "stat"])), Err(1));
+        assert_eq!(
+            buffer.text(),
+            "test: unknown command \"stat\", did you mean \"status\"?\nUsage: test COMMAND\nTry 'test --help' for more information.\n"
+        );
+
+        assert_eq!(command.try_execute(args(&["test", "world"])), Ok(()));
+        assert!(!buffer.text().contains("world"));
+
+        let mut secret = Command::new("secret description", "", |_, _, _| Ok(()));
+        secret.hidden();
+        command.add_subcommand("secret", secret);
+        assert_eq!(command.try_execute(args(&["test", "secre"])), Ok(()));
+        assert!(!buffer.text().contains("secret"));
+    }
+
+    #[test]
+    fn update_flags_deprecated() {
+        let buffer = Buffer::default();
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.error_writer(Box::new(buffer.clone()));
+        command.add_flag(Flag::new_bool("", "colour", "test deprecated").deprecated("use --color"));
+        command.update_flags(vec!["test", "--colour"]).unwrap();
+        assert_eq!(
+            buffer.text(),
+            "warning: --colour is deprecated: use --color\n"
+        );
+        assert_eq!(command.flags[2].value, FlagValue::Bool(true));
+    }
+
+    #[test]
+    fn update_flags_normalize_unicode() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.add_flag(Flag::new_bool("", "cafe", "test bool"));
+        command.add_flag(Flag::new_string("", "naïve", "test string"));
+        assert!(command.update_flags(vec!["test", "--café"]).is_err());
+
+        command.normalize_unicode();
+        command.reset_flags();
+        command
+            .update_flags(vec!["test", "--café", "--naive=x"])
+            .unwrap();
+        assert_eq!(command.flags[2].value, FlagValue::Bool(true));
+        assert_eq!(
+            command.flags[3].value,
+            FlagValue::String(Some(String::from("x")))
+        );
+        command.reset_flags();
+        command.update_flags(vec!["test", "--cafe\u{301}"]).unwrap();
+        assert_eq!(command.flags[2].value, FlagValue::Bool(true));
+        assert!(command.update_flags(vec!["test", "--Cafe"]).is_err());
+    }
+
+    #[test]
+    fn update_flags_normalize_flags() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.add_flag(Flag::new_int("m", "max-size", "test int"));
+        command.add_flag(Flag::new_bool("", "max-http", "test bool"));
+        assert_eq!(
+            command.update_flags(vec!["test", "--maxSize", "3"]),
+            Err(String::from(
+                "unknown flag --maxSize, did you mean --max-size?"
+            ))
+        );
+
+        command.normalize_flags();
+        command.reset_flags();
+        command
+            .update_flags(vec!["test", "--maxSize", "3", "--maxHTTP"])
+            .unwrap();
+        assert_eq!(command.flags[2].value, FlagValue::Int(Some(3)));
+        assert_eq!(command.flags[3].value, FlagValue::Bool(true));
+        command.update_flags(vec!["test", "--maxSize=4"]).unwrap();
+        assert_eq!(command.flags[2].value, FlagValue::Int(Some(4)));
+        assert!(command.update_flags(vec!["test", "-maxSize"]).is_err());
+    }
+
+    #[test]
+    fn update_flags_negatable() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.add_flag(
+            Flag::new_bool("c", "color", "test negatable")
+                .default_value(FlagValue::Bool(true))
+                .negatable(),
+        );
+        command.add_flag(Flag::new_bool("x", "xray", "test bool"));
+        command.reset_flags();
+        command.update_flags(vec!["test", "--no-color"]).unwrap();
+        assert_eq!(command.flags[2].value, FlagValue::Bool(false));
+        command.update_flags(vec!["test", "--color"]).unwrap();
+        assert_eq!(command.flags[2].value, FlagValue::Bool(true));
+        assert!(command.update_flags(vec!["test", "--no-xray"]).is_err());
+    }
+
+    #[test]
+    fn update_flags_negatable_literal() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.add_flag(
+            Flag::new_bool("c", "color", "test negatable")
+                .default_value(FlagValue::Bool(true))
+                .negatable(),
+        );
+        command.add_flag(Flag::new_bool("", "no-color", "test literal").no_negation());
+        command.reset_flags();
+        command.update_flags(vec!["test", "--no-color"]).unwrap();
+        assert_eq!(command.flags[2].value, FlagValue::Bool(true));
+        assert_eq!(command.flags[3].value, FlagValue::Bool(true));
+    }
+
+    #[test]
+    fn update_flags_bool_valued() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.add_flag(Flag::new_bool_valued("c", "color", "test bool valued"));
+        for (raw, value) in [
+            ("true", true),
+            ("TRUE", true),
+            ("yes", true),
+            ("Yes", true),
+            ("1", true),
+            ("false", false),
+            ("False", false),
+            ("no", false),
+            ("NO", false),
+            ("0", false),
+            ("on", true),
+            ("n", false),
+        ] {
+            command.flags[2].value = FlagValue::Bool(!value);
+            let simple_args = command.update_flags(vec!["test", "--color", raw]).unwrap();
+            assert_eq!(simple_args, vec!["test"]);
+            assert_eq!(command.flags[2].value, FlagValue::Bool(value));
+        }
+
+        command.reset_flags();
+        let simple_args = command.update_flags(vec!["test", "--color"]).unwrap();
+        assert_eq!(simple_args, vec!["test"]);
+        assert_eq!(command.flags[2].value, FlagValue::Bool(true));
+
+        command.reset_flags();
+        command.update_flags(vec!["test", "--color", "-h"]).unwrap();
+        assert_eq!(command.flags[2].value, FlagValue::Bool(true));
+        assert_eq!(command.flags[0].value, FlagValue::Bool(true));
+
+        assert_eq!(
+            command.update_flags(vec!["test", "--color", "maybe"]),
+            Err(String::from(
+                "invalid value \"maybe\" for flag --color: expected one of true, false, yes, no, on, off, y, n, 1, 0"
+            ))
+        );
+    }
+
+    #[test]
+    fn update_flags_optional_value() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.add_flag(Flag::new_optional_string("c", "color", "test optional"));
+        command.add_flag(Flag::new_bool("x", "xray", "test bool"));
+        command.update_flags(vec!["test"]).unwrap();
+        assert_eq!(command.flags[2].value, FlagValue::String(None));
+
+        for input in [
+            vec!["test", "--color"],
+            vec!["test", "--color", "-x"],
+            vec!["test", "--color="],
+        ] {
+            command.reset_flags();
+            command.update_flags(input).unwrap();
+            assert_eq!(
+                command.flags[2].value,
+                FlagValue::String(Some(String::new()))
+            );
+        }
+
+        for input in [
+            vec!["test", "--color=always"],
+            vec!["test", "--color", "always"],
+            vec!["test", "-c", "always"],
+        ] {
+            command.reset_flags();
+            let simple_args = command.update_flags(input).unwrap();
+            assert_eq!(simple_args, vec!["test"]);
+            assert_eq!(
+                command.flags[2].value,
+                FlagValue::String(Some(String::from("always")))
+            );
+        }
+    }
+
+    #[test]
+    fn update_flags_attached_long_value() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.add_flag(Flag::new_int("j", "jobs", "test int"));
+        command.add_flag(Flag::new_bool("x", "xray", "test bool"));
+        command.update_flags(vec!["test", "--jobs=4"]).unwrap();
+        assert_eq!(command.flags[2].value, FlagValue::Int(Some(4)));
+        command.add_flag(Flag::new_count("d", "debug", "test count"));
+        assert_eq!(
+            command.update_flags(vec!["test", "--debug=1"]),
+            Err(String::from("flag --debug does not take a value"))
+        );
+        assert_eq!(
+            command.update_flags(vec!["test", "--foo=1"]),
+            Err(String::from("unknown flag --foo=1"))
+        );
+    }
+
+    #[test]
+    fn update_flags_attached_bool_value() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.add_flag(Flag::new_bool("x", "xray", "test bool"));
+        command.update_flags(vec!["test", "--xray=1"]).unwrap();
+        assert_eq!(command.flags[2].value, FlagValue::Bool(true));
+        command.update_flags(vec!["test", "--xray=off"]).unwrap();
+        assert_eq!(command.flags[2].value, FlagValue::Bool(false));
+        command.update_flags(vec!["test", "--xray="]).unwrap();
+        assert_eq!(command.flags[2].value, FlagValue::Bool(true));
+        assert_eq!(
+            command.update_flags(vec!["test", "--xray=maybe"]),
+            Err(String::from(
+                "invalid value \"maybe\" for flag --xray: expected one of true, false, yes, no, on, off, y, n, 1, 0"
+            ))
+        );
+
+        for arg in ["--help=off", "--version=no", "--version="] {
+            let name = arg.split_once('=').unwrap().0;
+            assert_eq!(
+                command.update_flags(vec!["test", arg]),
+                Err(format!("flag {} does not take a value", name))
+            );
+        }
+    }
+
+    #[test]
+    fn run_once() {
+        let mut command = Command::new("test description", "test TEXT", |args, flags, _| {
+            match (&args[..], &flags[0].value) {
+                ([FlagValue::String(Some(arg))], FlagValue::Bool(true)) if arg == "world" => Ok(()),
+                _ => Err("unexpected args".into()),
+            }
+        });
+        command.add_flag(Flag::new_bool("x", "xray", "test bool"));
+        assert!(command.run_once(args(&["test", "-x", "world"])).is_ok());
+        assert!(matches!(
+            command.run_once(args(&["test", "world"])),
+            Err(CommandError::Run(err)) if err.to_string() == "unexpected args"
+        ));
+        assert!(matches!(
+            command.run_once(args(&["test", "--foo"])),
+            Err(CommandError::Parse(message)) if message == "unknown flag --foo"
+        ));
+
+        let mut command = Command::new("test description", "test COMMAND", |_, _, _| {
+            Err("root ran".into())
+        });
+        command.add_flag(Flag::new_string("n", "name", "test string").required());
+        command.config_loader(Box::new(TestConfigLoader(vec![(
+            String::from("name"),
+            ConfigValue::String(String::from("ferris")),
+        )])));
+        command.add_subcommand(
+            "sub",
+            Command::new("sub description", "TEXT", |_, _, _| Err("sub ran".into())),
+        );
+        assert!(matches!(
+            command.run_once(args(&["test", "sub"])),
+            Err(CommandError::Run(err)) if err.to_string() == "sub ran"
+        ));
+        assert_eq!(
+            command.flag_value("name"),
+            Some(&FlagValue::String(Some(String::from("ferris"))))
+        );
+    }
+
+    #[test]
+    fn parse_args_with() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| {
+            Err("unexpected run".into())
+        });
+        command.add_flag(Flag::new_string("n", "name", "test string"));
+        command.positional("count", FlagValue::Int(None));
+        command.args_exact(1);
+        command.kv_positionals();
+        command.writer(Box::new(io::sink()));
+        command.error_writer(Box::new(io::sink()));
+        let (parsed, flags) = command
+            .parse_args_with(args(&["test", "-n", "x", "3", "k=v"]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(parsed.values, vec![FlagValue::Int(Some(3))]);
+        assert_eq!(parsed.pairs, vec![(String::from("k"), String::from("v"))]);
+        assert_eq!(flags.get_str("name"), Some("x"));
+        assert!(flags.get("help").is_none());
+        assert!(matches!(
+            command.parse_args_with(args(&["test"])),
+            Err(CommandError::Parse(_))
+        ));
+        assert!(command
+            .parse_args_with(args(&["test", "-h"]))
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn try_execute_interactive() {
+        let error_buffer = Buffer::default();
+        let mut command = Command::new("test description", "test", |_, flags, _| {
+            match (flags.get_str("name"), flags.get_int("count")) {
+                (Some("world"), Some(3)) => Ok(()),
+                _ => Err("unexpected flags".into()),
+            }
+        });
+        command.error_writer(Box::new(error_buffer.clone()));
+        command.add_flag(Flag::new_string("n", "name", "test string").required());
+        command.add_flag(Flag::new_int("c", "count", "test int").required());
+        command.args_exact(0);
+        command.interactive();
+        command.input(Box::new(Cursor::new("world\r\n")));
+        assert_eq!(command.try_execute(args(&["test", "-c", "3"])), Err(1));
+        assert!(error_buffer
+            .text()
+            .starts_with("test: flag --name is required\n"));
+
+        let error_buffer = Buffer::default();
+        command.error_writer(Box::new(error_buffer.clone()));
+        command.input_is_stdin = true;
+        command.stdin_is_terminal = || true;
+        assert_eq!(command.try_execute(args(&["test", "-c", "3"])), Ok(()));
+        assert_eq!(error_buffer.text(), "Enter value for --name: ");
+    }
+
+    #[test]
+    fn try_execute_stdin_fallback() {
+        let buffer = Buffer::default();
+        let mut command = Command::new("test description", "test TEXT", |args, _, out| {
+            writeln!(out, "hello, {}!", args[0])?;
+            Ok(())
+        });
+        command.writer(Box::new(buffer.clone()));
+        command.stdin_fallback();
+        command.input(Box::new(Cursor::new("world\n")));
+        assert_eq!(command.try_execute(args(&["test"])), Ok(()));
+        assert_eq!(buffer.text(), "hello, world!\n");
+
+        let buffer = Buffer::default();
+        command.writer(Box::new(buffer.clone()));
+        command.input_is_stdin = true;
+        command.stdin_is_terminal = || true;
+        assert_eq!(command.try_execute(args(&["test"])), Ok(()));
+        assert_eq!(buffer.text(), format!("{}\n", command.help_string()));
+    }
+
+    #[test]
+    fn run_with_input() {
+        let mut command =
+            Command::new(
+                "test description",
+                "test A B",
+                |args, _, _| match &args[..] {
+                    [FlagValue::String(Some(a)), FlagValue::String(Some(b))]
+                        if a == "x" && b == "hello\nworld" =>
+                    {
+                        Ok(())
+                    }
+                    _ => Err("unexpected args".into()),
+                },
+            );
+        command.args_exact(2);
+        assert!(matches!(
+            command.run_with_input(args(&["test", "x", "-"]), Cursor::new("hello\nworld\n")),
+            Err(CommandError::Run(_))
+        ));
+
+        command.dash_stdin();
+        assert!(command
+            .run_with_input(args(&["test", "x", "-"]), Cursor::new("hello\nworld\n"))
+            .is_ok());
+        assert!(matches!(
+            command.run_with_input(args(&["test", "-", "-"]), Cursor::new("")),
+            Err(CommandError::Parse(message)) if message == "stdin can only be read by one - argument"
+        ));
+
+        let text = String::from("hello\nworld\n");
+        assert!(command
+            .run_with_input(args(&["test", "x", "-"]), text.as_bytes())
+            .is_ok());
+        assert!(command.input_is_stdin);
+    }
+
+    #[test]
+    fn update_flags_value_from_file() {
+        let path = env::temp_dir().join("cancer_test_update_flags_value_from_file");
+        fs::write(&path, "secret\n").unwrap();
+        let value = format!("@{}", path.display());
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.add_flag(Flag::new_string("t", "token", "test string"));
+        command
+            .update_flags(vec!["test", "--token", &value])
+            .unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(
+            command.flags[2].value,
+            FlagValue::String(Some(String::from("secret")))
+        );
+
+        let result = command.update_flags(vec!["test", "--token", &value]);
+        assert!(result
+            .unwrap_err()
+            .starts_with("failed reading value for flag --token from file"));
+    }
+
+    #[test]
+    fn run_once_response_file() {
+        let path = env::temp_dir().join("cancer_test_run_once_response_file");
+        fs::write(&path, "-x --name \"a b\"\n'c d' e\n").unwrap();
+        let value = format!("@{}", path.display());
+        let mut command = Command::new("test description", "test TEXT", |args, flags, _| {
+            match (&args[..], flags.get_str("name"), flags.get_bool("xray")) {
+                (
+                    [FlagValue::String(Some(a)), FlagValue::String(Some(b)), FlagValue::String(Some(c))],
+                    Some("a b"),
+                    Some(true),
+                ) if a == "c d" && b == "e" && c == "@f" => Ok(()),
+                _ => Err("unexpected args".into()),
+            }
+        });
+        command.add_flag(Flag::new_bool("x", "xray", "test bool"));
+        command.add_flag(Flag::new_string("n", "name", "test string"));
+        command.args_exact(3);
+        command.response_files();
+        assert!(command.run_once(args(&["test", &value, "@@f"])).is_ok());
+
+        let (parsed, flags) = command
+            .parse_args_with(args(&["test", "-n", &value, "a", "b", "c"]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(parsed.len(), 3);
+        assert_eq!(flags.get_str("name"), Some("-x --name \"a b\"\n'c d' e"));
+        fs::remove_file(&path).unwrap();
+
+        assert!(matches!(
+            command.run_once(args(&["test", "@cancer_test_missing_response_file"])),
+            Err(CommandError::Parse(message))
+                if message.starts_with("failed reading response file cancer_test_missing_response_file")
+        ));
+    }
+
+    #[test]
+    fn split_tokens() {
+        assert_eq!(
+            super::split_tokens(" a  \"b c\"\t'd \"e\"'f\n").unwrap(),
+            vec!["a", "b c", "d \"e\"f"]
+        );
+        assert_eq!(super::split_tokens("\"\"").unwrap(), vec![""]);
+        assert_eq!(super::split_tokens("a \"b"), None);
+    }
+
+    #[test]
+    fn update_flags_value_escaped_at() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.add_flag(Flag::new_string("t", "token", "test string"));
+        command
+            .update_flags(vec!["test", "--token", "@@handle"])
+            .unwrap();
+        assert_eq!(
+            command.flags[2].value,
+            FlagValue::String(Some(String::from("@handle")))
+        );
+    }
+
+    #[test]
+    fn run_once_positionals() {
+        let mut command =
+            Command::new(
+                "test description",
+                "test A B",
+                |args, _, _| match &args[..] {
+                    [FlagValue::Int(Some(3)), FlagValue::Int(Some(4))] => Ok(()),
+                    _ => Err("unexpected args".into()),
+                },
+            );
+        command.positional("a", FlagValue::Int(None));
+        command.positional("b", FlagValue::Int(None));
+        assert!(command.run_once(args(&["test", "3", "4"])).is_ok());
+        assert!(matches!(
+            command.run_once(args(&["test", "x", "4"])),
+            Err(CommandError::Parse(message)) if message == "argument a: invalid integer \"x\""
+        ));
+    }
+
+    #[test]
+    fn try_execute_args_exact() {
+        let buffer = Buffer::default();
+        let mut command = Command::new("test description", "test A B", |_, _, _| Ok(()));
+        command.error_writer(Box::new(buffer.clone()));
+        command.args_exact(2);
+        assert_eq!(command.try_execute(args(&["test", "a"])), Err(1));
+        assert_eq!(
+            buffer.text(),
+            "test: expected 2 arguments, got 1\nUsage: test A B\nTry 'test --help' for more information.\n"
+        );
+        assert_eq!(command.try_execute(args(&["test", "a", "b"])), Ok(()));
+    }
+
+    #[test]
+    fn run_once_args_range() {
+        let mut command = Command::new("test description", "test A [B]", |args, _, _| {
+            match args.len() {
+                1 | 2 => Ok(()),
+                _ => Err("unexpected args".into()),
+            }
+        });
+        command.args_range(1, 2);
+        assert!(command.run_once(args(&["test", "a"])).is_ok());
+        assert!(command.run_once(args(&["test", "a", "b"])).is_ok());
+        assert!(matches!(
+            command.run_once(args(&["test"])),
+            Err(CommandError::Parse(message)) if message == "expected 1 to 2 arguments, got 0"
+        ));
+
+        command.args_min(1);
+        assert!(matches!(
+            command.run_once(args(&["test"])),
+            Err(CommandError::Parse(message)) if message == "expected at least 1 argument, got 0"
+        ));
+    }
+
+    #[test]
+    fn args_variadic() {
+        let mut command = Command::new("test description", "test FILE", |args, _, _| {
+            let files: Vec<String> = args.iter().map(|x| x.to_string()).collect();
+            if files.len() == 5 {
+                Ok(())
+            } else {
+                Err(files.join(",").into())
+            }
+        });
+        command.positional("FILE", FlagValue::String(None));
+        command.args_variadic();
+        assert_eq!(command.usage_line(), "test FILE...");
+        assert!(command
+            .run_once(args(&["test", "a", "b", "c", "d", "e"]))
+            .is_ok());
     }
 }
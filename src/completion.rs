@@ -0,0 +1,244 @@
+use std::str::FromStr;
+
+use crate::command::Command;
+
+/// A shell which completion scripts are generated for
+///
+/// # Example
+///
+/// ```
+/// # use cancer::{command::Command, completion::Shell};
+/// let command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(()));
+/// let script = command.completion(Shell::Bash);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Shell {
+    /// Bash, completion file named `app.bash`
+    Bash,
+    /// Zsh, completion file named `_app`
+    Zsh,
+    /// Fish, completion file named `app.fish`
+    Fish,
+}
+
+impl Shell {
+    /// Returns the conventional completion file name of a program
+    ///
+    /// # Arguments
+    ///
+    /// `name` - A string slice that holds the program name
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::completion::Shell;
+    /// assert_eq!("_hello", Shell::Zsh.file_name("hello"));
+    /// assert_eq!("hello.bash", Shell::Bash.file_name("hello"));
+    /// ```
+    pub fn file_name(&self, name: &str) -> String {
+        match self {
+            Shell::Bash => format!("{}.bash", name),
+            Shell::Zsh => format!("_{}", name),
+            Shell::Fish => format!("{}.fish", name),
+        }
+    }
+}
+
+impl FromStr for Shell {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bash" => Ok(Shell::Bash),
+            "zsh" => Ok(Shell::Zsh),
+            "fish" => Ok(Shell::Fish),
+            _ => Err(format!(
+                "unknown shell \"{}\", expected one of bash, zsh, fish",
+                s
+            )),
+        }
+    }
+}
+
+/// Returns the completion script of a command, which completes subcommands and flags not hidden from help
+///
+/// # Arguments
+///
+/// `command` - The command which the script completes
+///
+/// `shell` - The shell which the script is written for
+pub(crate) fn completion_script(command: &Command, shell: Shell) -> String {
+    match shell {
+        Shell::Bash => bash_script(command),
+        Shell::Zsh => zsh_script(command),
+        Shell::Fish => fish_script(command),
+    }
+}
+
+/// Returns candidates with descriptions, subcommands first then flags with their prefixes
+fn candidates(command: &Command) -> Vec<(String, String)> {
+    let mut candidates: Vec<(String, String)> = command
+        .subcommands()
+        .filter(|(_, x)| !x.is_hidden())
+        .map(|(name, subcommand)| (String::from(name), subcommand.description.clone()))
+        .collect();
+    for flag in command
+        .flags
+        .iter()
+        .chain(command.global_flags.iter())
+        .filter(|x| !x.hidden)
+    {
+        if !flag.long.is_empty() {
+            candidates.push((
+                format!("{}{}", command.flag_long_start, flag.long),
+                flag.description.clone(),
+            ));
+        }
+        if !flag.short.is_empty() {
+            candidates.push((
+                format!("{}{}", command.flag_short_start, flag.short),
+                flag.description.clone(),
+            ));
+        }
+    }
+
+    candidates
+}
+
+/// Returns the program name as a shell function name
+fn function_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|x| if x.is_ascii_alphanumeric() { x } else { '_' })
+        .collect();
+    format!("_{}", name)
+}
+
+/// Returns text quoted by single quotes for bash and zsh
+fn quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "'\\''"))
+}
+
+fn bash_script(command: &Command) -> String {
+    let words: Vec<String> = candidates(command).into_iter().map(|(x, _)| x).collect();
+    let function = function_name(&command.name);
+
+    format!(
+        "{function}() {{\n    local cur=\"${{COMP_WORDS[COMP_CWORD]}}\"\n    COMPREPLY=($(compgen -W {} -- \"$cur\"))\n}}\n\ncomplete -F {function} {}\n",
+        quote(&words.join(" ")),
+        command.name,
+        function = function
+    )
+}
+
+fn zsh_script(command: &Command) -> String {
+    let function = function_name(&command.name);
+    let mut text = format!(
+        "#compdef {}\n\n{}() {{\n    local -a candidates\n    candidates=(\n",
+        command.name, function
+    );
+    for (word, description) in candidates(command) {
+        text.push_str(&format!(
+            "        {}\n",
+            quote(&format!("{}:{}", word.replace(':', "\\:"), description))
+        ));
+    }
+    text.push_str(&format!(
+        "    )\n    _describe {} candidates\n}}\n\n{} \"$@\"\n",
+        quote(&command.name),
+        function
+    ));
+
+    text
+}
+
+fn fish_script(command: &Command) -> String {
+    let quote = |text: &str| format!("'{}'", text.replace('\\', "\\\\").replace('\'', "\\'"));
+    let mut text = String::from("");
+    for (name, subcommand) in command.subcommands().filter(|(_, x)| !x.is_hidden()) {
+        text.push_str(&format!(
+            "complete -c {} -f -n __fish_use_subcommand -a {} -d {}\n",
+            command.name,
+            quote(name),
+            quote(&subcommand.description)
+        ));
+    }
+    for flag in command
+        .flags
+        .iter()
+        .chain(command.global_flags.iter())
+        .filter(|x| !x.hidden)
+    {
+        let mut options = String::from("");
+        if !flag.short.is_empty() {
+            options.push_str(&format!(" -s {}", flag.short));
+        }
+        if !flag.long.is_empty() {
+            options.push_str(&format!(" -l {}", flag.long));
+        }
+        text.push_str(&format!(
+            "complete -c {}{} -d {}\n",
+            command.name,
+            options,
+            quote(&flag.description)
+        ));
+    }
+
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flag::Flag;
+
+    fn command() -> Command {
+        let mut command =
+            Command::new("test description", "test TEXT", |_, _, _| Ok(())).name("test");
+        command.add_flag(Flag::new_bool("x", "xray", "test 'bool'"));
+        command.add_flag(Flag::new_bool("s", "secret", "test hidden").hidden());
+        command.add_subcommand(
+            "sub",
+            Command::new("sub description", "TEXT", |_, _, _| Ok(())),
+        );
+        command
+    }
+
+    #[test]
+    fn shell_from_str() {
+        assert_eq!("zsh".parse(), Ok(Shell::Zsh));
+        assert_eq!(
+            "ksh".parse::<Shell>(),
+            Err(String::from(
+                "unknown shell \"ksh\", expected one of bash, zsh, fish"
+            ))
+        );
+    }
+
+    #[test]
+    fn bash_script() {
+        let script = completion_script(&command(), Shell::Bash);
+        assert!(script.starts_with("_test() {\n"));
+        assert!(script.contains("compgen -W 'sub --help -h --version -v --xray -x' -- \"$cur\""));
+        assert!(script.ends_with("complete -F _test test\n"));
+    }
+
+    #[test]
+    fn zsh_script() {
+        let script = completion_script(&command(), Shell::Zsh);
+        assert!(script.starts_with("#compdef test\n"));
+        assert!(script.contains("        'sub:sub description'\n"));
+        assert!(script.contains("        '--xray:test '\\''bool'\\'''\n"));
+        assert!(!script.contains("--secret"));
+    }
+
+    #[test]
+    fn fish_script() {
+        let script = completion_script(&command(), Shell::Fish);
+        assert!(script.contains(
+            "complete -c test -f -n __fish_use_subcommand -a 'sub' -d 'sub description'\n"
+        ));
+        assert!(script.contains("complete -c test -s x -l xray -d 'test \\'bool\\''\n"));
+        assert!(!script.contains("secret"));
+    }
+}
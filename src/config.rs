@@ -0,0 +1,289 @@
+use std::{fs, io, path::PathBuf};
+
+/// A value of a config file
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConfigValue {
+    /// A boolean value, e.g. `true`
+    Bool(bool),
+    /// A string value, e.g. `"x"`
+    String(String),
+    /// A number value, e.g. `3` or `2.7`
+    Number(f64),
+}
+
+/// A trait that supports for loading flag values from a config file
+///
+/// # Example
+///
+/// ```
+/// # use cancer::config::{ConfigLoader, ConfigValue};
+/// pub struct CustomConfigLoader {}
+///
+/// impl ConfigLoader for CustomConfigLoader {
+///     fn load(&self) -> Result<Vec<(String, ConfigValue)>, String> {
+///         Ok(vec![(String::from("name"), ConfigValue::String(String::from("x")))])
+///     }
+/// }
+/// ```
+pub trait ConfigLoader {
+    /// Load config values
+    ///
+    /// # Return
+    ///
+    /// Config values with their keys, which are matched to flag long identifiers, or an error message
+    fn load(&self) -> Result<Vec<(String, ConfigValue)>, String>;
+}
+
+/// A type that supports for loading a flat JSON object from a file
+///
+/// # Example
+///
+/// ```text
+/// {"name": "x", "count": 3}
+/// ```
+pub struct JsonConfigLoader {
+    /// Path of the JSON file
+    path: PathBuf,
+    /// Whether a missing file loads no value instead of failing
+    optional: bool,
+}
+
+impl JsonConfigLoader {
+    /// Returns a `JsonConfigLoader` object
+    ///
+    /// # Arguments
+    ///
+    /// `path` - The path of the JSON file
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::config::JsonConfigLoader;
+    /// let loader = JsonConfigLoader::new("hello.json");
+    /// ```
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            optional: false,
+        }
+    }
+
+    /// Returns the loader which loads no value when the file is missing
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::config::JsonConfigLoader;
+    /// let loader = JsonConfigLoader::new("hello.json").optional();
+    /// ```
+    pub fn optional(mut self) -> Self {
+        self.optional = true;
+        self
+    }
+}
+
+impl ConfigLoader for JsonConfigLoader {
+    fn load(&self) -> Result<Vec<(String, ConfigValue)>, String> {
+        let text = match fs::read_to_string(&self.path) {
+            Err(err) if self.optional && err.kind() == io::ErrorKind::NotFound => {
+                return Ok(vec![])
+            }
+            text => text,
+        };
+        let text = text.map_err(|err| {
+            format!(
+                "failed reading config file {}: {}",
+                self.path.display(),
+                err
+            )
+        })?;
+
+        parse_json(&text)
+            .map_err(|err| format!("invalid config file {}: {}", self.path.display(), err))
+    }
+}
+
+/// Parse a flat JSON object, keys with `null` values are skipped
+///
+/// # Arguments
+///
+/// `text` - A string slice that holds the JSON object
+///
+/// # Return
+///
+/// Values with their keys in order, or an error message when the text is not a flat JSON object
+pub fn parse_json(text: &str) -> Result<Vec<(String, ConfigValue)>, String> {
+    let mut parser = JsonParser {
+        chars: text.chars().collect(),
+        position: 0,
+    };
+    let mut values = vec![];
+
+    parser.expect('{')?;
+    if parser.peek() == Some('}') {
+        parser.position += 1;
+    } else {
+        loop {
+            let key = parser.string()?;
+            parser.expect(':')?;
+            if let Some(value) = parser.value()? {
+                values.push((key, value));
+            }
+            match parser.next() {
+                Some(',') => continue,
+                Some('}') => break,
+                _ => return Err(parser.error("expected , or }")),
+            }
+        }
+    }
+    if parser.peek().is_some() {
+        return Err(parser.error("unexpected trailing characters"));
+    }
+
+    Ok(values)
+}
+
+/// A hand-written parser of flat JSON objects
+struct JsonParser {
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl JsonParser {
+    /// Returns the next non-whitespace char without consuming it
+    fn peek(&mut self) -> Option<char> {
+        while self
+            .chars
+            .get(self.position)
+            .is_some_and(|x| x.is_whitespace())
+        {
+            self.position += 1;
+        }
+        self.chars.get(self.position).copied()
+    }
+
+    /// Consume and returns the next non-whitespace char
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += 1;
+        Some(c)
+    }
+
+    /// Consume the next non-whitespace char, which must be `expected`
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        match self.next() {
+            Some(c) if c == expected => Ok(()),
+            _ => Err(self.error(&format!("expected {}", expected))),
+        }
+    }
+
+    /// Returns an error message with the current position
+    fn error(&self, message: &str) -> String {
+        format!("{} at position {}", message, self.position)
+    }
+
+    /// Parse a value, `None` for `null`
+    fn value(&mut self) -> Result<Option<ConfigValue>, String> {
+        match self.peek() {
+            Some('"') => Ok(Some(ConfigValue::String(self.string()?))),
+            Some('{') | Some('[') => Err(self.error("nested values are not supported")),
+            Some(c) if c == '-' || c.is_ascii_alphanumeric() => {
+                let start = self.position;
+                while self.chars.get(self.position).is_some_and(|x| {
+                    *x == '-' || *x == '+' || *x == '.' || x.is_ascii_alphanumeric()
+                }) {
+                    self.position += 1;
+                }
+                let literal: String = self.chars[start..self.position].iter().collect();
+                match &literal[..] {
+                    "true" => Ok(Some(ConfigValue::Bool(true))),
+                    "false" => Ok(Some(ConfigValue::Bool(false))),
+                    "null" => Ok(None),
+                    _ => literal
+                        .parse()
+                        .map(|x| Some(ConfigValue::Number(x)))
+                        .map_err(|_| self.error(&format!("invalid value {}", literal))),
+                }
+            }
+            _ => Err(self.error("expected a value")),
+        }
+    }
+
+    /// Parse a string with escapes
+    fn string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut text = String::new();
+
+        loop {
+            let c = match self.chars.get(self.position) {
+                Some(c) => *c,
+                None => return Err(self.error("unterminated string")),
+            };
+            self.position += 1;
+            match c {
+                '"' => return Ok(text),
+                '\\' => {
+                    let escaped = self.chars.get(self.position).copied();
+                    self.position += 1;
+                    match escaped {
+                        Some('"') => text.push('"'),
+                        Some('\\') => text.push('\\'),
+                        Some('/') => text.push('/'),
+                        Some('n') => text.push('\n'),
+                        Some('t') => text.push('\t'),
+                        Some('r') => text.push('\r'),
+                        Some('b') => text.push('\u{8}'),
+                        Some('f') => text.push('\u{c}'),
+                        Some('u') => {
+                            let hex: String =
+                                self.chars.iter().skip(self.position).take(4).collect();
+                            let c = u32::from_str_radix(&hex, 16)
+                                .ok()
+                                .and_then(char::from_u32)
+                                .ok_or_else(|| self.error("invalid unicode escape"))?;
+                            self.position += 4;
+                            text.push(c);
+                        }
+                        _ => return Err(self.error("invalid escape")),
+                    }
+                }
+                _ => text.push(c),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_json_flat() {
+        let values = parse_json(
+            r#" {"name": "x \"y\"", "count": 3, "ratio": -2.5, "on": true, "off": null} "#,
+        )
+        .unwrap();
+        assert_eq!(
+            values,
+            vec![
+                (
+                    String::from("name"),
+                    ConfigValue::String(String::from("x \"y\""))
+                ),
+                (String::from("count"), ConfigValue::Number(3.0)),
+                (String::from("ratio"), ConfigValue::Number(-2.5)),
+                (String::from("on"), ConfigValue::Bool(true)),
+            ]
+        );
+        assert_eq!(parse_json("{}").unwrap(), vec![]);
+    }
+
+    #[test]
+    fn parse_json_invalid() {
+        assert!(parse_json(r#"{"name": {"nested": 1}}"#)
+            .unwrap_err()
+            .starts_with("nested values are not supported"));
+        assert!(parse_json(r#"{"name": "x""#).is_err());
+        assert!(parse_json(r#"["x"]"#).is_err());
+    }
+}
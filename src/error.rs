@@ -0,0 +1,62 @@
+use std::{error::Error, fmt};
+
+/// An error type that represents for command failures
+///
+/// # Example
+///
+/// ```
+/// # use cancer::error::CommandError;
+/// let err = CommandError::Parse(String::from("unknown flag --foo"));
+/// ```
+#[derive(Debug)]
+pub enum CommandError {
+    /// A parse error that represents for invalid args, e.g. an unknown flag or an invalid flag value
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use cancer::error::CommandError;
+    /// let err = CommandError::Parse(String::from("unknown flag --foo"));
+    /// ```
+    Parse(String),
+    /// A run error that represents for an error returned by the command execution logic
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use cancer::error::CommandError;
+    /// let err = CommandError::Run("something went wrong".into());
+    /// ```
+    Run(Box<dyn Error>),
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandError::Parse(message) => f.write_str(message),
+            CommandError::Run(err) => err.fmt(f),
+        }
+    }
+}
+
+impl Error for CommandError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CommandError::Parse(_) => None,
+            CommandError::Run(err) => Some(err.as_ref()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display() {
+        let err = CommandError::Parse(String::from("unknown flag --foo"));
+        assert_eq!(err.to_string(), "unknown flag --foo");
+        let err = CommandError::Run("test error".into());
+        assert_eq!(err.to_string(), "test error");
+    }
+}
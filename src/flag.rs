@@ -1,22 +1,26 @@
-use std::fmt;
+use std::{fmt, mem, ops::Deref};
+
+use crate::util::parse_bool;
 
 pub const FLAG_SHORT_START: &str = "-";
 pub const FLAG_LONG_START: &str = "--";
 
-/// An enum that represents for flag value, which includes boolean, string, int and float
+/// An enum that represents for flag value, which includes boolean, string, int, float and string list
 ///
 /// # Example
 ///
 /// ```
+/// # use cancer::flag::FlagValue;
 /// let value = FlagValue::Bool(true);
 /// ```
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum FlagValue {
     /// An boolean enum that represents for `bool` flag value
     ///
     /// # Example
     ///
     /// ```
+    /// # use cancer::flag::FlagValue;
     /// let value = FlagValue::Bool(true);
     /// ```
     Bool(bool),
@@ -25,6 +29,7 @@ pub enum FlagValue {
     /// # Example
     ///
     /// ```
+    /// # use cancer::flag::FlagValue;
     /// let value = FlagValue::String(Some(String::from("Hello, world!")));
     /// ```
     String(Option<String>),
@@ -33,6 +38,7 @@ pub enum FlagValue {
     /// # Example
     ///
     /// ```
+    /// # use cancer::flag::FlagValue;
     /// let value = FlagValue::Int(Some(3));
     /// ```
     Int(Option<i32>),
@@ -41,13 +47,110 @@ pub enum FlagValue {
     /// # Example
     ///
     /// ```
+    /// # use cancer::flag::FlagValue;
     /// let value = FlagValue::Float(Some(2.7));
     /// ```
     Float(Option<f32>),
+    /// A string list enum that represents for `Vec<String>` flag value
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use cancer::flag::FlagValue;
+    /// let value = FlagValue::StringList(Some(vec![String::from("-x"), String::from("y")]));
+    /// ```
+    StringList(Option<Vec<String>>),
+}
+
+impl FlagValue {
+    /// Parse a raw value according to the current variant and store it
+    ///
+    /// `true`, `false`, `yes`, `no`, `on`, `off`, `y`, `n`, `1` and `0` are accepted case-insensitively for a bool,
+    /// a string list holds the raw value as its only item
+    ///
+    /// # Arguments
+    ///
+    /// `raw` - A string slice that holds the raw value
+    ///
+    /// # Return
+    ///
+    /// `Ok(())` when stored, or an error message like `expected an integer` when `raw` is invalid,
+    /// the value is unchanged on error
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use cancer::flag::FlagValue;
+    /// let mut value = FlagValue::Int(None);
+    /// value.parse_into("3").unwrap();
+    ///
+    /// assert_eq!(FlagValue::Int(Some(3)), value);
+    /// assert_eq!(Err(String::from("expected an integer")), value.parse_into("x"));
+    /// ```
+    pub fn parse_into(&mut self, raw: &str) -> Result<(), String> {
+        *self = match self {
+            FlagValue::Bool(_) => match parse_bool(raw) {
+                Some(value) => FlagValue::Bool(value),
+                None => {
+                    return Err(String::from(
+                        "expected one of true, false, yes, no, on, off, y, n, 1, 0",
+                    ))
+                }
+            },
+            FlagValue::String(_) => FlagValue::String(Some(String::from(raw))),
+            FlagValue::Int(_) => raw
+                .parse()
+                .map(|x| FlagValue::Int(Some(x)))
+                .map_err(|_| String::from("expected an integer"))?,
+            FlagValue::Float(_) => raw
+                .parse()
+                .map(|x| FlagValue::Float(Some(x)))
+                .map_err(|_| String::from("expected a float"))?,
+            FlagValue::StringList(_) => FlagValue::StringList(Some(vec![String::from(raw)])),
+        };
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for FlagValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FlagValue::Bool(value) => write!(f, "{}", value),
+            FlagValue::String(value) => write!(f, "{}", value.as_deref().unwrap_or("")),
+            FlagValue::Int(Some(value)) => write!(f, "{}", value),
+            FlagValue::Float(Some(value)) => write!(f, "{}", value),
+            FlagValue::StringList(Some(values)) => write!(f, "{}", values.join(" ")),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// An enum that represents for where a flag value comes from, checked by flag relations so that a default
+/// value neither conflicts with nor satisfies other flags
+///
+/// # Example
+///
+/// ```
+/// # use cancer::flag::{Flag, ValueSource};
+/// let flag = Flag::new_bool("f", "ferris", "say hello from ferris");
+///
+/// assert_eq!(ValueSource::Default, flag.source);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ValueSource {
+    /// The default value of the flag
+    Default,
+    /// A config file
+    Config,
+    /// An environment variable
+    Env,
+    /// Args, or a value prompted for interactively
+    Args,
 }
 
 /// A command flag type
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Flag {
     /// Flag short identifier
     ///
@@ -78,14 +181,52 @@ pub struct Flag {
     /// `FlagValue::Int` - default value is `0`
     ///
     /// `FlagValue::Float` - default value is `0.0`
+    ///
+    /// `FlagValue::StringList` - default value is `None`
     pub value: FlagValue,
+    /// Flag default value which the value is reset to before parsing, same as the initial value by default
+    pub default: FlagValue,
+    /// Source of the value, `ValueSource::Default` until the value is updated by parsing
+    pub source: ValueSource,
+    /// Name of the environment variable which the flag falls back to when not provided in args
+    pub env: Option<String>,
+    /// Whether the flag captures every following arg, flags included, and stops parsing
+    pub trailing: bool,
+    /// Whether the bool flag takes an explicit value like `true` or `false` from the following arg
+    pub bool_valued: bool,
+    /// Whether the string flag may be used bare or with a value, `Some("")` when used bare
+    pub optional_value: bool,
+    /// Whether the int flag takes no value and counts its occurrences, e.g. `3` for `-vvv`
+    pub count: bool,
+    /// Whether the flag is inherited by subcommands and can be used at any subcommand level
+    pub global: bool,
+    /// Delimiter which the value of a string list flag is split on, e.g. `,` for `a,b,c`
+    pub delimiter: Option<char>,
+    /// Separator which a string list flag value from an environment variable is split on, e.g. `:` for `/a:/b`,
+    /// the delimiter is used when not set
+    pub list_separator: Option<char>,
+    /// Short or long identifiers of flags which can not be set along with the flag
+    pub conflicts: Vec<String>,
+    /// Short or long identifiers of flags which must be set along with the flag
+    pub requires: Vec<String>,
+    /// Deprecation message which is warned when the flag is used, e.g. `use --color`
+    pub deprecated: Option<String>,
+    /// Whether the flag is hidden from help
+    pub hidden: bool,
+    /// Whether the flag must be set by args, an environment variable or a config file
+    pub required: bool,
+    /// Whether the bool flag can be set to `false` by its long identifier prefixed with `no-`, e.g. `--no-color`
+    pub negatable: bool,
+    /// Whether the flag is a built-in help, help all or version flag added by the command
+    pub(crate) reserved: bool,
 }
 
 impl fmt::Display for Flag {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.write_fmt(format_args!(
-            "  {}{}, {}{}\t{}",
-            FLAG_SHORT_START, self.short, FLAG_LONG_START, self.long, self.description
+            "  {}\t{}",
+            self.names(FLAG_SHORT_START, FLAG_LONG_START),
+            self.description
         ))
     }
 }
@@ -105,7 +246,7 @@ impl Flag {
     ///
     /// # Examples
     ///
-    /// ```
+    /// ```ignore
     /// let flag = Flag::new("f", "ferris", "say hello from ferris", FlagValue::Bool(false));
     /// ```
     fn new(short: &str, long: &str, description: &str, value: FlagValue) -> Self {
@@ -113,10 +254,71 @@ impl Flag {
             short: String::from(short),
             long: String::from(long),
             description: String::from(description),
-            value,
+            value: value.clone(),
+            default: value,
+            source: ValueSource::Default,
+            env: None,
+            trailing: false,
+            bool_valued: false,
+            optional_value: false,
+            count: false,
+            global: false,
+            delimiter: None,
+            list_separator: None,
+            conflicts: vec![],
+            requires: vec![],
+            deprecated: None,
+            hidden: false,
+            required: false,
+            negatable: false,
+            reserved: false,
         }
     }
 
+    /// Returns a flag with only a long identifier, the value and the arguments provided
+    ///
+    /// # Arguments
+    ///
+    /// `long` - A string slice that holds the long identifier
+    ///
+    /// `description` - A string slice that holds the description
+    ///
+    /// `value` - An enum that holds the value
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::flag::{Flag, FlagValue};
+    /// let flag = Flag::new_long("ferris", "say hello from ferris", FlagValue::Bool(false));
+    ///
+    /// assert_eq!("--ferris", flag.names("-", "--"));
+    /// ```
+    pub fn new_long(long: &str, description: &str, value: FlagValue) -> Self {
+        Flag::new("", long, description, value)
+    }
+
+    /// Returns a flag with only a short identifier, the value and the arguments provided
+    ///
+    /// # Arguments
+    ///
+    /// `short` - A string slice that holds the short identifier
+    ///
+    /// `description` - A string slice that holds the description
+    ///
+    /// `value` - An enum that holds the value
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::flag::{Flag, FlagValue};
+    /// let flag = Flag::new_short("f", "say hello from ferris", FlagValue::Bool(false));
+    ///
+    /// assert_eq!("-f", flag.names("-", "--"));
+    /// ```
+    pub fn new_short(short: &str, description: &str, value: FlagValue) -> Self {
+        Flag::new(short, "", description, value)
+    }
+
     /// Returns a flag with bool(`false` by default) value and the arguments provided
     ///
     /// # Arguments
@@ -130,12 +332,39 @@ impl Flag {
     /// # Examples
     ///
     /// ```
+    /// # use cancer::flag::Flag;
     /// let flag = Flag::new_bool("f", "ferris", "say hello from ferris");
     /// ```
     pub fn new_bool(short: &str, long: &str, description: &str) -> Self {
         Flag::new(short, long, description, FlagValue::Bool(false))
     }
 
+    /// Returns a flag with bool(`false` by default) value and the arguments provided,
+    /// which takes an explicit value from the following arg
+    ///
+    /// `true`, `false`, `yes`, `no`, `on`, `off`, `y`, `n`, `1` and `0` are accepted case-insensitively,
+    /// the value is `true` when the flag is not followed by a value
+    ///
+    /// # Arguments
+    ///
+    /// `short` - A string slice that holds the short identifier
+    ///
+    /// `long` - A string slice that holds the long identifier
+    ///
+    /// `description` - A string slice that holds the description
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::flag::Flag;
+    /// let flag = Flag::new_bool_valued("c", "color", "colorize output");
+    /// ```
+    pub fn new_bool_valued(short: &str, long: &str, description: &str) -> Self {
+        let mut flag = Flag::new(short, long, description, FlagValue::Bool(false));
+        flag.bool_valued = true;
+        flag
+    }
+
     /// Returns a flag with string(`None` by default) value and the arguments provided
     ///
     /// # Arguments
@@ -149,12 +378,39 @@ impl Flag {
     /// # Examples
     ///
     /// ```
+    /// # use cancer::flag::Flag;
     /// let flag = Flag::new_string("f", "ferris", "say hello from ferris");
     /// ```
     pub fn new_string(short: &str, long: &str, description: &str) -> Self {
         Flag::new(short, long, description, FlagValue::String(None))
     }
 
+    /// Returns a flag with string(`None` by default) value and the arguments provided,
+    /// which may be used bare or followed by a value, e.g. `--color`, `--color always` or `--color=always`
+    ///
+    /// The value is `None` when the flag is not used, `Some("")` when used bare and `Some(value)` otherwise,
+    /// the following arg is taken as the value unless it is a flag
+    ///
+    /// # Arguments
+    ///
+    /// `short` - A string slice that holds the short identifier
+    ///
+    /// `long` - A string slice that holds the long identifier
+    ///
+    /// `description` - A string slice that holds the description
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::flag::Flag;
+    /// let flag = Flag::new_optional_string("c", "color", "colorize output, WHEN is auto by default");
+    /// ```
+    pub fn new_optional_string(short: &str, long: &str, description: &str) -> Self {
+        let mut flag = Flag::new(short, long, description, FlagValue::String(None));
+        flag.optional_value = true;
+        flag
+    }
+
     /// Returns a flag with int(`None` by default) value and the arguments provided
     ///
     /// # Arguments
@@ -168,12 +424,38 @@ impl Flag {
     /// # Examples
     ///
     /// ```
+    /// # use cancer::flag::Flag;
     /// let flag = Flag::new_int("f", "ferris", "say hello from ferris");
     /// ```
     pub fn new_int(short: &str, long: &str, description: &str) -> Self {
         Flag::new(short, long, description, FlagValue::Int(None))
     }
 
+    /// Returns a flag with int(`None` by default) value and the arguments provided,
+    /// which takes no value and counts how many times it is used
+    ///
+    /// Every occurrence increments the count, also within short flag clusters, e.g. `-vqv` counts `-v` twice
+    ///
+    /// # Arguments
+    ///
+    /// `short` - A string slice that holds the short identifier
+    ///
+    /// `long` - A string slice that holds the long identifier
+    ///
+    /// `description` - A string slice that holds the description
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::flag::Flag;
+    /// let flag = Flag::new_count("d", "debug", "increase debug output");
+    /// ```
+    pub fn new_count(short: &str, long: &str, description: &str) -> Self {
+        let mut flag = Flag::new(short, long, description, FlagValue::Int(None));
+        flag.count = true;
+        flag
+    }
+
     /// Returns a flag with float(`None` by default) value and the arguments provided
     ///
     /// # Arguments
@@ -187,101 +469,981 @@ impl Flag {
     /// # Examples
     ///
     /// ```
+    /// # use cancer::flag::Flag;
     /// let flag = Flag::new_float("f", "ferris", "say hello from ferris");
     /// ```
     pub fn new_float(short: &str, long: &str, description: &str) -> Self {
         Flag::new(short, long, description, FlagValue::Float(None))
     }
-}
 
-impl Flag {
-    /// Returns if provided arg match flag
+    /// Returns a trailing flag with string list(`None` by default) value and the arguments provided,
+    /// which captures every following arg, flags included, and stops parsing
     ///
     /// # Arguments
     ///
-    /// `arg` - A string slice that holds argument that needs to check
+    /// `short` - A string slice that holds the short identifier
+    ///
+    /// `long` - A string slice that holds the long identifier
+    ///
+    /// `description` - A string slice that holds the description
     ///
     /// # Examples
     ///
     /// ```
-    /// // matched scenario
-    /// let flag = Flag::new("f", "ferris", "say hello from ferris", FlagValue::Bool(false));
-    /// let result = flag.is_match("-f");
+    /// # use cancer::flag::Flag;
+    /// let flag = Flag::new_trailing("a", "args", "args passed to the program");
+    /// ```
+    pub fn new_trailing(short: &str, long: &str, description: &str) -> Self {
+        let mut flag = Flag::new(short, long, description, FlagValue::StringList(None));
+        flag.trailing = true;
+        flag
+    }
+
+    /// Returns a flag with string list(`None` by default) value and the arguments provided,
+    /// which takes a comma-separated value like `a,b,c`
     ///
-    /// assert_eq!(true, result);
+    /// Empty segments like the one in `a,,b` are dropped, and `\,` is a literal comma
     ///
-    /// // non-matched scenario
-    /// let flag = Flag::new("f", "ferris", "say hello from ferris", FlagValue::Bool(false));
-    /// let result = flag.is_match("--rollercoaster");
+    /// # Arguments
+    ///
+    /// `short` - A string slice that holds the short identifier
+    ///
+    /// `long` - A string slice that holds the long identifier
+    ///
+    /// `description` - A string slice that holds the description
+    ///
+    /// # Examples
     ///
-    /// assert_eq!(false, result);
     /// ```
-    pub fn is_match(&self, arg: &str) -> bool {
-        arg == format!("{}{}", FLAG_SHORT_START, self.short)
-            || arg == format!("{}{}", FLAG_LONG_START, self.long)
+    /// # use cancer::flag::Flag;
+    /// let flag = Flag::new_string_list_csv("t", "tags", "tags of the greeting");
+    /// ```
+    pub fn new_string_list_csv(short: &str, long: &str, description: &str) -> Self {
+        let mut flag = Flag::new(short, long, description, FlagValue::StringList(None));
+        flag.delimiter = Some(',');
+        flag
     }
 }
 
-/// Returns if provided arg is a flag
-///
-/// # Arguments
-///
-/// `arg` - A string slice that holds argument that needs to check
-///
-/// # Examples
-///
-/// ```
-/// // flag scenario
-/// let result = is_flag("-f");
-///
-/// assert_eq!(true, result);
-///
-/// // not flag scenario
-/// let result = is_flag("f");
-///
-/// assert_eq!(false, result);
-/// ```
-pub fn is_flag(arg: &str) -> bool {
-    arg.starts_with(FLAG_SHORT_START) || arg.starts_with(FLAG_LONG_START)
-}
+impl Flag {
+    /// Returns the flag with a default value, which the value is reset to before parsing
+    ///
+    /// `{long}` tokens in the default of a string flag are substituted by the values of other flags after parsing,
+    /// e.g. `{name}.png` becomes `ferris.png` with `--name ferris`
+    ///
+    /// # Arguments
+    ///
+    /// `value` - A `FlagValue` of the same type as the flag value
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::flag::{Flag, FlagValue};
+    /// let flag = Flag::new_int("p", "port", "port to listen on").default_value(FlagValue::Int(Some(8080)));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value` is not of the same type as the flag value
+    pub fn default_value(mut self, value: FlagValue) -> Self {
+        assert!(
+            mem::discriminant(&self.value) == mem::discriminant(&value),
+            "default value of flag {} has a mismatched type",
+            self.long
+        );
+        self.value = value.clone();
+        self.default = value;
+        self
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Reset the flag value to its default value and the source to `ValueSource::Default`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::flag::{Flag, FlagValue};
+    /// let mut flag = Flag::new_bool("f", "ferris", "say hello from ferris");
+    /// flag.value = FlagValue::Bool(true);
+    /// flag.reset();
+    ///
+    /// assert_eq!(FlagValue::Bool(false), flag.value);
+    /// ```
+    pub fn reset(&mut self) {
+        self.value = self.default.clone();
+        self.source = ValueSource::Default;
+    }
 
-    #[test]
-    fn new() {
-        let short = "t";
-        let long = "test";
-        let description = "test description";
-        let value = FlagValue::Bool(false);
-        let flag = Flag::new(short, long, description, value);
-        assert_eq!(flag.short, short);
-        assert_eq!(flag.long, long);
-        assert_eq!(flag.description, description);
+    /// Returns the flag with an environment variable which the flag falls back to when not provided in args
+    ///
+    /// # Arguments
+    ///
+    /// `name` - A string slice that holds the environment variable name
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::flag::Flag;
+    /// let flag = Flag::new_int("p", "port", "port to listen on").env("HELLO_PORT");
+    /// ```
+    pub fn env(mut self, name: &str) -> Self {
+        self.env = Some(String::from(name));
+        self
     }
 
-    #[test]
-    fn new_bool() {
-        let flag = Flag::new_bool("t", "test", "test description");
-        assert_eq!(flag.value, FlagValue::Bool(false));
+    /// Returns the string list flag with a separator which a value from an environment variable is split on
+    ///
+    /// # Arguments
+    ///
+    /// `separator` - A char which the value is split on, e.g. `:` for `/a:/b:/c`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::flag::Flag;
+    /// let flag = Flag::new_string_list_csv("p", "paths", "paths to search")
+    ///     .env("HELLO_PATHS")
+    ///     .list_separator(':');
+    /// ```
+    pub fn list_separator(mut self, separator: char) -> Self {
+        self.list_separator = Some(separator);
+        self
     }
 
-    #[test]
-    fn new_string() {
-        let flag = Flag::new_string("t", "test", "test description");
-        assert_eq!(flag.value, FlagValue::String(None));
+    /// Returns the flag marked as global, which is inherited by subcommands and can be used at any subcommand level
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::flag::Flag;
+    /// let flag = Flag::new_bool("V", "verbose", "verbose output").global();
+    /// ```
+    pub fn global(mut self) -> Self {
+        self.global = true;
+        self
     }
 
-    #[test]
-    fn new_int() {
-        let flag = Flag::new_int("t", "test", "test description");
-        assert_eq!(flag.value, FlagValue::Int(None));
+    /// Returns the flag which can not be set along with another flag
+    ///
+    /// Only values provided by args, environment variables or config files count, default values are ignored
+    ///
+    /// # Arguments
+    ///
+    /// `name` - A string slice that holds the short or long identifier of the other flag
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::flag::Flag;
+    /// let flag = Flag::new_bool("q", "quiet", "quiet output").conflicts_with("verbose");
+    /// ```
+    pub fn conflicts_with(mut self, name: &str) -> Self {
+        self.conflicts.push(String::from(name));
+        self
     }
 
-    #[test]
-    fn new_float() {
-        let flag = Flag::new_float("t", "test", "test description");
-        assert_eq!(flag.value, FlagValue::Float(None));
+    /// Returns the flag which must be set along with another flag
+    ///
+    /// Only values provided by args, environment variables or config files count, default values are ignored
+    ///
+    /// # Arguments
+    ///
+    /// `name` - A string slice that holds the short or long identifier of the other flag
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::flag::Flag;
+    /// let flag = Flag::new_string("p", "password", "login password").requires("user");
+    /// ```
+    pub fn requires(mut self, name: &str) -> Self {
+        self.requires.push(String::from(name));
+        self
+    }
+
+    /// Returns the flag which must be set by args, an environment variable or a config file
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::flag::Flag;
+    /// let flag = Flag::new_string("n", "name", "name to greet").required();
+    /// ```
+    pub fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+
+    /// Returns the bool flag which can be set to `false` by its long identifier prefixed with `no-`,
+    /// e.g. `--no-color`, which is listed as `--[no-]color` in help
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::flag::Flag;
+    /// let flag = Flag::new_bool("c", "color", "colorize output").negatable();
+    /// ```
+    pub fn negatable(mut self) -> Self {
+        assert!(
+            matches!(self.value, FlagValue::Bool(_)) && !self.long.is_empty(),
+            "negatable flag {} must be a bool flag with a long identifier",
+            self.long
+        );
+        self.negatable = true;
+        self
+    }
+
+    /// Returns the flag which never participates in `--no-` negation, e.g. for a literal `no-*` flag
+    /// or to undo `negatable`, flags are not negatable by default
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::flag::Flag;
+    /// let flag = Flag::new_bool("c", "color", "colorize output").negatable().no_negation();
+    ///
+    /// assert!(!flag.negatable);
+    /// ```
+    pub fn no_negation(mut self) -> Self {
+        self.negatable = false;
+        self
+    }
+
+    /// Returns the flag marked as deprecated, which still works but warns with the message when used
+    ///
+    /// # Arguments
+    ///
+    /// `message` - A string slice that holds the deprecation message, e.g. the replacement of the flag
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::flag::Flag;
+    /// let flag = Flag::new_bool("", "colour", "colored output").deprecated("use --color").hidden();
+    /// ```
+    pub fn deprecated(mut self, message: &str) -> Self {
+        self.deprecated = Some(String::from(message));
+        self
+    }
+
+    /// Returns the flag marked as a built-in help, help all or version flag
+    pub(crate) fn reserved(mut self) -> Self {
+        self.reserved = true;
+        self
+    }
+
+    /// Returns the flag hidden from help
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::flag::Flag;
+    /// let flag = Flag::new_bool("", "debug", "debug output").hidden();
+    /// ```
+    pub fn hidden(mut self) -> Self {
+        self.hidden = true;
+        self
+    }
+}
+
+impl Flag {
+    /// Returns if provided arg match flag
+    ///
+    /// # Arguments
+    ///
+    /// `arg` - A string slice that holds argument that needs to check
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// // matched scenario
+    /// let flag = Flag::new("f", "ferris", "say hello from ferris", FlagValue::Bool(false));
+    /// let result = flag.is_match("-f");
+    ///
+    /// assert_eq!(true, result);
+    ///
+    /// // non-matched scenario
+    /// let flag = Flag::new("f", "ferris", "say hello from ferris", FlagValue::Bool(false));
+    /// let result = flag.is_match("--rollercoaster");
+    ///
+    /// assert_eq!(false, result);
+    /// ```
+    pub fn is_match(&self, arg: &str) -> bool {
+        self.is_match_with(arg, FLAG_SHORT_START, FLAG_LONG_START)
+    }
+
+    /// Returns if provided arg match flag with the prefixes provided
+    ///
+    /// # Arguments
+    ///
+    /// `arg` - A string slice that holds argument that needs to check
+    ///
+    /// `short_start` - A string slice that holds the prefix of short identifier
+    ///
+    /// `long_start` - A string slice that holds the prefix of long identifier
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::flag::Flag;
+    /// let flag = Flag::new_bool("f", "ferris", "say hello from ferris");
+    /// let result = flag.is_match_with("/f", "/", "//");
+    ///
+    /// assert_eq!(true, result);
+    /// ```
+    pub fn is_match_with(&self, arg: &str, short_start: &str, long_start: &str) -> bool {
+        (!self.short.is_empty() && arg == format!("{}{}", short_start, self.short))
+            || (!self.long.is_empty() && arg == format!("{}{}", long_start, self.long))
+    }
+
+    /// Returns the short and long identifiers with the prefixes provided, like `-c, --[no-]color` for a negatable flag,
+    /// an empty identifier is omitted, e.g. `--ferris` for a flag without short identifier
+    ///
+    /// # Arguments
+    ///
+    /// `short_start` - A string slice that holds the prefix of short identifier
+    ///
+    /// `long_start` - A string slice that holds the prefix of long identifier
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::flag::Flag;
+    /// let flag = Flag::new_bool("f", "ferris", "say hello from ferris");
+    /// let names = flag.names("-", "--");
+    ///
+    /// assert_eq!("-f, --ferris", names);
+    /// ```
+    pub fn names(&self, short_start: &str, long_start: &str) -> String {
+        let negation = if self.negatable { "[no-]" } else { "" };
+        let short = format!("{}{}", short_start, self.short);
+        let long = format!("{}{}{}", long_start, negation, self.long);
+        match (self.short.is_empty(), self.long.is_empty()) {
+            (true, false) => long,
+            (false, true) => short,
+            _ => format!("{}, {}", short, long),
+        }
+    }
+
+    /// Returns if the short or long identifier of the flag is `name`
+    ///
+    /// # Arguments
+    ///
+    /// `name` - A string slice that holds the identifier without prefix
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::flag::Flag;
+    /// let flag = Flag::new_bool("f", "ferris", "say hello from ferris");
+    ///
+    /// assert_eq!(true, flag.is_named("ferris"));
+    /// ```
+    pub fn is_named(&self, name: &str) -> bool {
+        (!self.short.is_empty() && self.short == name)
+            || (!self.long.is_empty() && self.long == name)
+    }
+
+    /// Returns the long identifier with the prefix provided, or the short identifier when the flag has no long one
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::flag::Flag;
+    /// let flag = Flag::new_bool("f", "ferris", "say hello from ferris");
+    ///
+    /// assert_eq!("--ferris", flag.display_name("-", "--"));
+    /// ```
+    pub fn display_name(&self, short_start: &str, long_start: &str) -> String {
+        if self.long.is_empty() {
+            format!("{}{}", short_start, self.short)
+        } else {
+            format!("{}{}", long_start, self.long)
+        }
+    }
+
+    /// Returns if the flag is set, which is a `true` bool flag or a value flag with `Some` value
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::flag::{Flag, FlagValue};
+    /// let mut flag = Flag::new_string("n", "name", "name to greet");
+    /// assert_eq!(false, flag.is_set());
+    ///
+    /// flag.value = FlagValue::String(Some(String::from("world")));
+    /// assert_eq!(true, flag.is_set());
+    /// ```
+    pub fn is_set(&self) -> bool {
+        match &self.value {
+            FlagValue::Bool(value) => *value,
+            FlagValue::String(value) => value.is_some(),
+            FlagValue::Int(value) => value.is_some(),
+            FlagValue::Float(value) => value.is_some(),
+            FlagValue::StringList(value) => value.is_some(),
+        }
+    }
+
+    /// Returns if the flag is set by args, an environment variable or a config file rather than by its default value
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::flag::{Flag, FlagValue, ValueSource};
+    /// let mut flag = Flag::new_int("p", "port", "port to listen on").default_value(FlagValue::Int(Some(8080)));
+    /// assert_eq!(false, flag.is_provided());
+    ///
+    /// flag.value = FlagValue::Int(Some(9090));
+    /// flag.source = ValueSource::Args;
+    /// assert_eq!(true, flag.is_provided());
+    /// ```
+    pub fn is_provided(&self) -> bool {
+        self.source != ValueSource::Default && self.is_set()
+    }
+}
+
+/// A flag set type that supports for looking up flag values by short or long identifier
+///
+/// # Example
+///
+/// ```
+/// # use cancer::flag::{Flag, FlagSet};
+/// let flag = Flag::new_bool("f", "ferris", "say hello from ferris");
+/// let flags = FlagSet::new(vec![&flag]);
+///
+/// assert_eq!(Some(false), flags.get_bool("ferris"));
+/// ```
+#[derive(Debug)]
+pub struct FlagSet<'a> {
+    /// Flags in the set
+    flags: Vec<&'a Flag>,
+}
+
+impl<'a> FlagSet<'a> {
+    /// Returns a flag set with the flags provided
+    ///
+    /// # Arguments
+    ///
+    /// `flags` - A vector of `Flag` references
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::flag::{Flag, FlagSet};
+    /// let flag = Flag::new_bool("f", "ferris", "say hello from ferris");
+    /// let flags = FlagSet::new(vec![&flag]);
+    /// ```
+    pub fn new(flags: Vec<&'a Flag>) -> Self {
+        Self { flags }
+    }
+
+    /// Returns the flag which short or long identifier is `name`
+    ///
+    /// # Arguments
+    ///
+    /// `name` - A string slice that holds the short or long identifier
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::flag::{Flag, FlagSet};
+    /// let flag = Flag::new_bool("f", "ferris", "say hello from ferris");
+    /// let flags = FlagSet::new(vec![&flag]);
+    ///
+    /// assert!(flags.get("f").is_some());
+    /// ```
+    pub fn get(&self, name: &str) -> Option<&'a Flag> {
+        self.flags[..].get_flag(name)
+    }
+
+    /// Returns the bool value of the flag named `name`, `None` when missing or not a bool flag
+    ///
+    /// # Arguments
+    ///
+    /// `name` - A string slice that holds the short or long identifier
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::flag::{Flag, FlagSet};
+    /// let flag = Flag::new_bool("f", "ferris", "say hello from ferris");
+    /// let flags = FlagSet::new(vec![&flag]);
+    ///
+    /// assert_eq!(Some(false), flags.get_bool("ferris"));
+    /// ```
+    pub fn get_bool(&self, name: &str) -> Option<bool> {
+        self.flags[..].get_bool(name)
+    }
+
+    /// Returns the string value of the flag named `name`, `None` when missing, unset or not a string flag
+    ///
+    /// # Arguments
+    ///
+    /// `name` - A string slice that holds the short or long identifier
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::flag::{Flag, FlagSet, FlagValue};
+    /// let mut flag = Flag::new_string("n", "name", "name to say hello to");
+    /// flag.value = FlagValue::String(Some(String::from("world")));
+    /// let flags = FlagSet::new(vec![&flag]);
+    ///
+    /// assert_eq!(Some("world"), flags.get_str("name"));
+    /// ```
+    pub fn get_str(&self, name: &str) -> Option<&'a str> {
+        self.flags[..].get_str(name)
+    }
+
+    /// Returns the int value of the flag named `name`, `None` when missing, unset or not an int flag
+    ///
+    /// # Arguments
+    ///
+    /// `name` - A string slice that holds the short or long identifier
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::flag::{Flag, FlagSet, FlagValue};
+    /// let mut flag = Flag::new_int("c", "count", "times to say hello");
+    /// flag.value = FlagValue::Int(Some(3));
+    /// let flags = FlagSet::new(vec![&flag]);
+    ///
+    /// assert_eq!(Some(3), flags.get_int("count"));
+    /// ```
+    pub fn get_int(&self, name: &str) -> Option<i32> {
+        self.flags[..].get_int(name)
+    }
+
+    /// Returns the float value of the flag named `name`, `None` when missing, unset or not a float flag
+    ///
+    /// # Arguments
+    ///
+    /// `name` - A string slice that holds the short or long identifier
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::flag::{Flag, FlagSet, FlagValue};
+    /// let mut flag = Flag::new_float("s", "scale", "scale of hello");
+    /// flag.value = FlagValue::Float(Some(2.5));
+    /// let flags = FlagSet::new(vec![&flag]);
+    ///
+    /// assert_eq!(Some(2.5), flags.get_float("scale"));
+    /// ```
+    pub fn get_float(&self, name: &str) -> Option<f32> {
+        self.flags[..].get_float(name)
+    }
+}
+
+/// Typed getters of flag values on a slice of flag references, like flags received by the logic of a command
+///
+/// # Example
+///
+/// ```
+/// # use cancer::flag::{Flag, FlagSliceExt, FlagValue};
+/// let mut flag = Flag::new_int("c", "count", "times to say hello");
+/// flag.value = FlagValue::Int(Some(3));
+/// let flags = [&flag];
+///
+/// assert_eq!(Some(3), flags[..].get_int("count"));
+/// ```
+pub trait FlagSliceExt<'a> {
+    /// Returns the flag which short or long identifier is `name`
+    fn get_flag(&self, name: &str) -> Option<&'a Flag>;
+
+    /// Returns the bool value of the flag named `name`, `None` when missing or not a bool flag
+    fn get_bool(&self, name: &str) -> Option<bool> {
+        match self.get_flag(name)?.value {
+            FlagValue::Bool(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the string value of the flag named `name`, `None` when missing, unset or not a string flag
+    fn get_str(&self, name: &str) -> Option<&'a str> {
+        match &self.get_flag(name)?.value {
+            FlagValue::String(value) => value.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Returns the int value of the flag named `name`, `None` when missing, unset or not an int flag
+    fn get_int(&self, name: &str) -> Option<i32> {
+        match self.get_flag(name)?.value {
+            FlagValue::Int(value) => value,
+            _ => None,
+        }
+    }
+
+    /// Returns the float value of the flag named `name`, `None` when missing, unset or not a float flag
+    fn get_float(&self, name: &str) -> Option<f32> {
+        match self.get_flag(name)?.value {
+            FlagValue::Float(value) => value,
+            _ => None,
+        }
+    }
+
+    /// Returns the string list value of the flag named `name`, `None` when missing, unset or not a string list flag
+    fn get_list(&self, name: &str) -> Option<&'a [String]> {
+        match &self.get_flag(name)?.value {
+            FlagValue::StringList(value) => value.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+/// A type built from parsed flags, which declares the flags it is built from
+///
+/// With the `derive` feature it is derived by `#[derive(cancer::Command)]` on a struct with named fields,
+/// each field becomes a flag whose type is picked by the field type and whose description is the doc comment
+///
+/// # Example
+///
+/// ```
+/// # use cancer::flag::{Flag, FlagSliceExt, FromFlags};
+/// struct Options {
+///     count: i32,
+/// }
+///
+/// impl FromFlags for Options {
+///     fn flags() -> Vec<Flag> {
+///         vec![Flag::new_int("c", "count", "times to say hello")]
+///     }
+///
+///     fn from_flags(flags: &[&Flag]) -> Self {
+///         Self {
+///             count: flags.get_int("count").unwrap_or_default(),
+///         }
+///     }
+/// }
+/// ```
+pub trait FromFlags: Sized {
+    /// Returns the flags which the type is built from
+    fn flags() -> Vec<Flag>;
+
+    /// Returns the value built from parsed flags, e.g. the flags passed to `run`
+    fn from_flags(flags: &[&Flag]) -> Self;
+}
+
+impl<'a> FlagSliceExt<'a> for [&'a Flag] {
+    fn get_flag(&self, name: &str) -> Option<&'a Flag> {
+        self.iter().find(|x| x.is_named(name)).copied()
+    }
+}
+
+impl<'a> Deref for FlagSet<'a> {
+    type Target = [&'a Flag];
+
+    fn deref(&self) -> &Self::Target {
+        &self.flags
+    }
+}
+
+/// Returns if provided arg is a flag
+///
+/// # Arguments
+///
+/// `arg` - A string slice that holds argument that needs to check
+///
+/// # Examples
+///
+/// ```
+/// # use cancer::flag::is_flag;
+/// // flag scenario
+/// let result = is_flag("-f");
+///
+/// assert_eq!(true, result);
+///
+/// // not flag scenario
+/// let result = is_flag("f");
+///
+/// assert_eq!(false, result);
+/// ```
+pub fn is_flag(arg: &str) -> bool {
+    is_flag_with(arg, FLAG_SHORT_START, FLAG_LONG_START)
+}
+
+/// Returns if provided arg is a flag with the prefixes provided
+///
+/// # Arguments
+///
+/// `arg` - A string slice that holds argument that needs to check
+///
+/// `short_start` - A string slice that holds the prefix of short identifier
+///
+/// `long_start` - A string slice that holds the prefix of long identifier
+///
+/// # Examples
+///
+/// ```
+/// # use cancer::flag::is_flag_with;
+/// let result = is_flag_with("/f", "/", "//");
+///
+/// assert_eq!(true, result);
+/// ```
+pub fn is_flag_with(arg: &str, short_start: &str, long_start: &str) -> bool {
+    arg.starts_with(short_start) || arg.starts_with(long_start)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new() {
+        let short = "t";
+        let long = "test";
+        let description = "test description";
+        let value = FlagValue::Bool(false);
+        let flag = Flag::new(short, long, description, value);
+        assert_eq!(flag.short, short);
+        assert_eq!(flag.long, long);
+        assert_eq!(flag.description, description);
+    }
+
+    #[test]
+    fn new_bool() {
+        let flag = Flag::new_bool("t", "test", "test description");
+        assert_eq!(flag.value, FlagValue::Bool(false));
+    }
+
+    #[test]
+    fn new_bool_valued() {
+        let flag = Flag::new_bool_valued("t", "test", "test description");
+        assert_eq!(flag.value, FlagValue::Bool(false));
+        assert!(flag.bool_valued);
+    }
+
+    #[test]
+    fn new_optional_string() {
+        let flag = Flag::new_optional_string("t", "test", "test description");
+        assert_eq!(flag.value, FlagValue::String(None));
+        assert!(flag.optional_value);
+    }
+
+    #[test]
+    fn new_count() {
+        let flag = Flag::new_count("t", "test", "test description");
+        assert_eq!(flag.value, FlagValue::Int(None));
+        assert!(flag.count);
+    }
+
+    #[test]
+    fn new_string() {
+        let flag = Flag::new_string("t", "test", "test description");
+        assert_eq!(flag.value, FlagValue::String(None));
+    }
+
+    #[test]
+    fn new_int() {
+        let flag = Flag::new_int("t", "test", "test description");
+        assert_eq!(flag.value, FlagValue::Int(None));
+    }
+
+    #[test]
+    fn new_float() {
+        let flag = Flag::new_float("t", "test", "test description");
+        assert_eq!(flag.value, FlagValue::Float(None));
+    }
+
+    #[test]
+    fn new_trailing() {
+        let flag = Flag::new_trailing("t", "test", "test description");
+        assert_eq!(flag.value, FlagValue::StringList(None));
+        assert!(flag.trailing);
+    }
+
+    #[test]
+    fn default_value() {
+        let mut flag =
+            Flag::new_int("t", "test", "test description").default_value(FlagValue::Int(Some(3)));
+        assert_eq!(flag.value, FlagValue::Int(Some(3)));
+        flag.value = FlagValue::Int(Some(4));
+        flag.reset();
+        assert_eq!(flag.value, FlagValue::Int(Some(3)));
+    }
+
+    #[test]
+    #[should_panic(expected = "default value of flag test has a mismatched type")]
+    fn default_value_mismatched() {
+        Flag::new_int("t", "test", "test description").default_value(FlagValue::Bool(true));
+    }
+
+    #[test]
+    fn is_match_with() {
+        let flag = Flag::new_bool("t", "test", "test description");
+        assert!(flag.is_match_with("/t", "/", "//"));
+        assert!(flag.is_match_with("//test", "/", "//"));
+        assert!(!flag.is_match_with("-t", "/", "//"));
+    }
+
+    #[test]
+    fn flag_set_getters() {
+        let mut bool_flag = Flag::new_bool("b", "bool", "test bool");
+        bool_flag.value = FlagValue::Bool(true);
+        let mut string_flag = Flag::new_string("s", "string", "test string");
+        string_flag.value = FlagValue::String(Some(String::from("test")));
+        let mut int_flag = Flag::new_int("i", "int", "test int");
+        int_flag.value = FlagValue::Int(Some(3));
+        let mut float_flag = Flag::new_float("f", "float", "test float");
+        float_flag.value = FlagValue::Float(Some(2.5));
+        let flags = FlagSet::new(vec![&bool_flag, &string_flag, &int_flag, &float_flag]);
+
+        assert_eq!(flags.get_bool("bool"), Some(true));
+        assert_eq!(flags.get_bool("b"), Some(true));
+        assert_eq!(flags.get_str("string"), Some("test"));
+        assert_eq!(flags.get_int("int"), Some(3));
+        assert_eq!(flags.get_float("float"), Some(2.5));
+        assert_eq!(flags.len(), 4);
+    }
+
+    #[test]
+    fn flag_set_getters_missing() {
+        let string_flag = Flag::new_string("s", "string", "test string");
+        let flags = FlagSet::new(vec![&string_flag]);
+
+        assert_eq!(flags.get_str("string"), None);
+        assert_eq!(flags.get_int("string"), None);
+        assert_eq!(flags.get_bool("missing"), None);
+        assert_eq!(flags.get_float("missing"), None);
+        assert!(flags.get("missing").is_none());
+    }
+
+    #[test]
+    fn flag_slice_ext() {
+        let mut int_flag = Flag::new_int("i", "int", "test int");
+        int_flag.value = FlagValue::Int(Some(3));
+        let mut list_flag = Flag::new_string_list_csv("l", "list", "test list");
+        list_flag.value = FlagValue::StringList(Some(vec![String::from("a")]));
+        let string_flag = Flag::new_string("s", "string", "test string");
+        let flags: &[&Flag] = &[&int_flag, &list_flag, &string_flag];
+
+        assert_eq!(flags.get_int("i"), Some(3));
+        assert_eq!(flags.get_list("list"), Some(&[String::from("a")][..]));
+        assert_eq!(flags.get_bool("int"), None);
+        assert_eq!(flags.get_str("string"), None);
+        assert_eq!(flags.get_float("missing"), None);
+        assert!(flags.get_flag("missing").is_none());
+
+        let mut short_flag = Flag::new_short("x", "test short", FlagValue::String(None));
+        short_flag.value = FlagValue::String(Some(String::from("x")));
+        let flags: &[&Flag] = &[&short_flag];
+        assert_eq!(flags.get_str("x"), Some("x"));
+        assert!(flags.get_flag("").is_none());
+    }
+
+    #[test]
+    fn parse_into() {
+        for (mut value, raw, expected) in [
+            (FlagValue::Bool(false), "Yes", FlagValue::Bool(true)),
+            (FlagValue::Bool(true), "0", FlagValue::Bool(false)),
+            (
+                FlagValue::String(None),
+                "x",
+                FlagValue::String(Some(String::from("x"))),
+            ),
+            (FlagValue::Int(None), "-3", FlagValue::Int(Some(-3))),
+            (
+                FlagValue::Float(Some(1.0)),
+                "2.5",
+                FlagValue::Float(Some(2.5)),
+            ),
+            (
+                FlagValue::StringList(None),
+                "x",
+                FlagValue::StringList(Some(vec![String::from("x")])),
+            ),
+        ] {
+            value.parse_into(raw).unwrap();
+            assert_eq!(value, expected);
+        }
+    }
+
+    #[test]
+    fn parse_into_invalid() {
+        let mut value = FlagValue::Float(Some(1.0));
+        assert_eq!(value.parse_into("x"), Err(String::from("expected a float")));
+        assert_eq!(value, FlagValue::Float(Some(1.0)));
+        assert_eq!(
+            FlagValue::Bool(false).parse_into("maybe"),
+            Err(String::from(
+                "expected one of true, false, yes, no, on, off, y, n, 1, 0"
+            ))
+        );
+    }
+
+    #[test]
+    fn flag_value_display() {
+        assert_eq!(FlagValue::Bool(true).to_string(), "true");
+        assert_eq!(
+            FlagValue::String(Some(String::from("world"))).to_string(),
+            "world"
+        );
+        assert_eq!(FlagValue::Int(Some(3)).to_string(), "3");
+        assert_eq!(FlagValue::Int(None).to_string(), "");
+        assert_eq!(
+            FlagValue::StringList(Some(vec![String::from("a"), String::from("b")])).to_string(),
+            "a b"
+        );
+    }
+
+    #[test]
+    fn names_single_identifier() {
+        let flag = Flag::new_long("ferris", "test long only", FlagValue::Bool(false));
+        assert_eq!(flag.names("-", "--"), "--ferris");
+        assert_eq!(flag.to_string(), "  --ferris\ttest long only");
+        assert!(!flag.is_match_with("-", "-", "--"));
+
+        let flag = Flag::new_short("f", "test short only", FlagValue::Int(None));
+        assert_eq!(flag.names("-", "--"), "-f");
+        assert_eq!(flag.to_string(), "  -f\ttest short only");
+        assert!(!flag.is_match_with("--", "-", "--"));
+    }
+
+    #[test]
+    fn names_negatable() {
+        let flag = Flag::new_bool("c", "color", "test description");
+        assert_eq!(flag.names("-", "--"), "-c, --color");
+        let flag = flag.negatable();
+        assert_eq!(flag.names("-", "--"), "-c, --[no-]color");
+    }
+
+    #[test]
+    fn is_set() {
+        let mut flag = Flag::new_bool("f", "ferris", "test bool");
+        assert!(!flag.is_set());
+        flag.value = FlagValue::Bool(true);
+        assert!(flag.is_set());
+
+        let mut flag = Flag::new_string("n", "name", "test string");
+        assert!(!flag.is_set());
+        flag.value = FlagValue::String(Some(String::new()));
+        assert!(flag.is_set());
+
+        let mut flag = Flag::new_int("c", "count", "test int");
+        assert!(!flag.is_set());
+        flag.value = FlagValue::Int(Some(0));
+        assert!(flag.is_set());
+
+        let mut flag = Flag::new_float("r", "ratio", "test float");
+        assert!(!flag.is_set());
+        flag.value = FlagValue::Float(Some(0.0));
+        assert!(flag.is_set());
+    }
+
+    #[test]
+    fn new_string_list_csv() {
+        let flag = Flag::new_string_list_csv("t", "tags", "test list");
+        assert_eq!(flag.value, FlagValue::StringList(None));
+        assert_eq!(flag.delimiter, Some(','));
+        assert!(!flag.trailing);
+    }
+
+    #[test]
+    fn global() {
+        let flag = Flag::new_bool("t", "test", "test description");
+        assert!(!flag.global);
+        let flag = flag.global();
+        assert!(flag.global);
     }
 }
@@ -1,12 +1,25 @@
-use crate::command::Command;
+use std::collections::HashMap;
+
+use crate::{
+    command::Command,
+    flag::{Flag, FlagValue},
+    util::{paint, term_width, use_color},
+};
+
+const USAGE_HEADER: &str = "usage_header";
+const COMMANDS_HEADER: &str = "commands_header";
+const FLAGS_HEADER: &str = "flags_header";
+const GLOBAL_FLAGS_HEADER: &str = "global_flags_header";
+const EXAMPLES_HEADER: &str = "examples_header";
 
 /// A trait that supports for help rendering
 ///
 /// # Example
 ///
 /// ```
+/// # use cancer::{command::Command, help::HelpRender};
 /// pub struct CustomHelpRender {}
-
+///
 /// impl HelpRender for CustomHelpRender {
 ///     fn help_text(&self, command: &Command) -> String {
 ///         format!("my custom help")
@@ -21,38 +34,868 @@ pub trait HelpRender {
 ///
 /// # Example
 ///
+/// ```text
 /// command description
 ///
 /// Usage:
 ///
 ///       command TEXT
 ///
+/// Commands:
+///
+///       sub    subcommand description
+///
 /// Flags:
 ///
-///       -h, --help        help for command
-///       -v, --version     version for command
-pub struct DefaultHelpRender {}
+///       -h, --help       help for command
+///       -v, --version    version for command
+///
+/// Examples:
+///
+///       command world    gives a hello to world
+/// ```
+///
+/// Section headings are bold when color is enabled, color is suppressed when the `NO_COLOR` environment variable
+/// is present unless color is forced
+///
+/// Flag descriptions are followed by `[default: X]` and `[env: VAR]` hints when a flag has a non-empty default value
+/// or falls back to an environment variable, and are wrapped to the width set by `width` or the terminal width
+#[derive(Default)]
+pub struct DefaultHelpRender {
+    /// Whether section headings are colored
+    color: bool,
+    /// Whether color is used regardless of `NO_COLOR`
+    force_color: bool,
+    /// Whether the trailing newline is kept, the writer adds the final newline
+    keep_trailing_newline: bool,
+    /// Width in columns which flag descriptions are wrapped to, the terminal width when not set
+    width: Option<usize>,
+}
 
 impl DefaultHelpRender {
     /// Returns a `DefaultHelpRender` object
     pub fn new() -> Self {
-        Self {}
+        Self {
+            color: false,
+            force_color: false,
+            keep_trailing_newline: false,
+            width: None,
+        }
+    }
+
+    /// Set the width in columns which flag descriptions are wrapped to, instead of the terminal width
+    ///
+    /// # Arguments
+    ///
+    /// `width` - The width in columns
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::help::DefaultHelpRender;
+    /// let render = DefaultHelpRender::new().width(100);
+    /// ```
+    pub fn width(mut self, width: usize) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    /// Set whether section headings are colored
+    ///
+    /// # Arguments
+    ///
+    /// `enabled` - A bool that holds whether color is enabled
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::help::DefaultHelpRender;
+    /// let render = DefaultHelpRender::new().color(true);
+    /// ```
+    pub fn color(mut self, enabled: bool) -> Self {
+        self.color = enabled;
+        self
+    }
+
+    /// Force color even when the `NO_COLOR` environment variable is present
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::help::DefaultHelpRender;
+    /// let render = DefaultHelpRender::new().force_color();
+    /// ```
+    pub fn force_color(mut self) -> Self {
+        self.force_color = true;
+        self
+    }
+
+    /// Set whether the trailing newline of help is trimmed, `true` by default as the final newline is added when printed
+    ///
+    /// # Arguments
+    ///
+    /// `trim` - A bool that holds whether the trailing newline is trimmed
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::help::DefaultHelpRender;
+    /// let render = DefaultHelpRender::new().trim_trailing(false);
+    /// ```
+    pub fn trim_trailing(mut self, trim: bool) -> Self {
+        self.keep_trailing_newline = !trim;
+        self
+    }
+
+    /// Returns a section heading line of the message key in English, in the header color of the command theme
+    /// when color is used
+    fn heading(&self, command: &Command, key: &str) -> String {
+        let color = use_color(self.color, self.force_color);
+        format!(
+            "{}\n",
+            paint(default_message(key), &command.get_theme().header, color)
+        )
     }
 }
 
 impl HelpRender for DefaultHelpRender {
     fn help_text(&self, command: &Command) -> String {
-        let mut text = String::from("");
-        text.push_str(&format!("{}\n", command.description));
+        let color = use_color(self.color, self.force_color);
+        let line_width = self.width.or_else(term_width);
+        let text = render_help(command, color, line_width, |key| self.heading(command, key));
+        trim_trailing_newline(text, self.keep_trailing_newline)
+    }
+}
+
+/// A type that supports for help rendering with section headings from message catalogs
+///
+/// Message keys are `usage_header`, `commands_header`, `flags_header`, `global_flags_header` and `examples_header`, a message is
+/// looked up in the catalog of the command locale, then the default catalog, then falls back to English
+///
+/// # Example
+///
+/// ```
+/// # use std::collections::HashMap;
+/// # use cancer::help::CatalogHelpRender;
+/// let mut catalog = HashMap::new();
+/// catalog.insert(String::from("flags_header"), String::from("Optionen:"));
+/// let render = CatalogHelpRender::new(HashMap::new()).locale_catalog("de", catalog);
+/// ```
+#[derive(Default)]
+pub struct CatalogHelpRender {
+    /// Default catalog used for any locale
+    catalog: HashMap<String, String>,
+    /// Catalogs of locales
+    locale_catalogs: HashMap<String, HashMap<String, String>>,
+    /// Whether the trailing newline is kept, the writer adds the final newline
+    keep_trailing_newline: bool,
+}
+
+impl CatalogHelpRender {
+    /// Returns a `CatalogHelpRender` object
+    ///
+    /// # Arguments
+    ///
+    /// `catalog` - A map from message keys to translated messages, used for any locale
+    pub fn new(catalog: HashMap<String, String>) -> Self {
+        Self {
+            catalog,
+            locale_catalogs: HashMap::new(),
+            keep_trailing_newline: false,
+        }
+    }
+
+    /// Add the catalog of a locale, which takes precedence over the default catalog when the command has the locale
+    ///
+    /// # Arguments
+    ///
+    /// `lang` - A string slice that holds the locale, e.g. `de`
+    ///
+    /// `catalog` - A map from message keys to translated messages
+    pub fn locale_catalog(mut self, lang: &str, catalog: HashMap<String, String>) -> Self {
+        self.locale_catalogs.insert(String::from(lang), catalog);
+        self
+    }
+
+    /// Set whether the trailing newline of help is trimmed, `true` by default as the final newline is added when printed
+    ///
+    /// # Arguments
+    ///
+    /// `trim` - A bool that holds whether the trailing newline is trimmed
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::collections::HashMap;
+    /// # use cancer::help::CatalogHelpRender;
+    /// let render = CatalogHelpRender::new(HashMap::new()).trim_trailing(false);
+    /// ```
+    pub fn trim_trailing(mut self, trim: bool) -> Self {
+        self.keep_trailing_newline = !trim;
+        self
+    }
+
+    /// Returns the message of the key for the locale of command
+    fn message(&self, command: &Command, key: &str) -> String {
+        command
+            .get_locale()
+            .and_then(|lang| self.locale_catalogs.get(lang))
+            .and_then(|catalog| catalog.get(key))
+            .or_else(|| self.catalog.get(key))
+            .cloned()
+            .unwrap_or_else(|| String::from(default_message(key)))
+    }
+}
+
+impl HelpRender for CatalogHelpRender {
+    fn help_text(&self, command: &Command) -> String {
+        let text = render_help(command, false, None, |key| {
+            format!("{}\n", self.message(command, key))
+        });
+        trim_trailing_newline(text, self.keep_trailing_newline)
+    }
+}
+
+/// Returns help text without the final newline added by the render unless it is kept,
+/// blank lines before it, e.g. at the end of `after_help`, are kept
+fn trim_trailing_newline(text: String, keep: bool) -> String {
+    match text.strip_suffix('\n') {
+        Some(trimmed) if !keep => String::from(trimmed),
+        _ => text,
+    }
+}
+
+/// Returns the English message of a message key
+fn default_message(key: &str) -> &str {
+    match key {
+        USAGE_HEADER => "Usage:",
+        COMMANDS_HEADER => "Commands:",
+        FLAGS_HEADER => "Flags:",
+        GLOBAL_FLAGS_HEADER => "Global Flags:",
+        EXAMPLES_HEADER => "Examples:",
+        _ => key,
+    }
+}
+
+/// Returns the full help text of command, ending with a newline
+///
+/// # Arguments
+///
+/// `command` - A `Command` to render
+///
+/// `color` - A bool that holds whether flag identifiers are colored by the command theme
+///
+/// `line_width` - The width in columns which flag descriptions are wrapped to, not wrapped when `None`
+///
+/// `heading` - A function which returns the section heading line of a message key
+fn render_help(
+    command: &Command,
+    color: bool,
+    line_width: Option<usize>,
+    heading: impl Fn(&str) -> String,
+) -> String {
+    let mut text = String::from("");
+    if let Some(before_help) = &command.before_help {
+        text.push_str(&format!("{}\n", before_help));
         text.push_str(&String::from("\n"));
-        text.push_str(&String::from("Usage:\n"));
-        text.push_str(&format!("  {}\n", command.usage));
+    }
+    text.push_str(&format!("{}\n", help_description(command)));
+    text.push_str(&String::from("\n"));
+    text.push_str(&heading(USAGE_HEADER));
+    text.push_str(&format!("  {}\n", command.usage_line()));
+    if visible_subcommands(command).next().is_some() {
         text.push_str(&String::from("\n"));
-        text.push_str(&String::from("Flags:\n"));
-        for flag in command.flags.iter() {
-            text.push_str(&format!("{}\n", flag));
+        text.push_str(&heading(COMMANDS_HEADER));
+        let width = subcommands_width(command);
+        for (name, subcommand) in visible_subcommands(command) {
+            text.push_str(&format!(
+                "  {:width$}    {}\n",
+                subcommand_label(name, subcommand),
+                subcommand.description,
+                width = width
+            ));
+        }
+    }
+    text.push_str(&String::from("\n"));
+    text.push_str(&heading(FLAGS_HEADER));
+    let width = flags_width(command);
+    for flag in visible_flags(command, &command.flags) {
+        text.push_str(&format!(
+            "{}\n",
+            flag_text(command, flag, width, color, line_width)
+        ));
+    }
+    if !command.global_flags.is_empty() {
+        text.push_str(&String::from("\n"));
+        text.push_str(&heading(GLOBAL_FLAGS_HEADER));
+        for flag in visible_flags(command, &command.global_flags) {
+            text.push_str(&format!(
+                "{}\n",
+                flag_text(command, flag, width, color, line_width)
+            ));
+        }
+    }
+    if !command.examples().is_empty() {
+        text.push_str(&String::from("\n"));
+        text.push_str(&heading(EXAMPLES_HEADER));
+        let width = command
+            .examples()
+            .iter()
+            .map(|(example, _)| example.chars().count())
+            .max()
+            .unwrap_or(0);
+        for (example, description) in command.examples() {
+            text.push_str(&format!(
+                "  {:width$}    {}\n",
+                example,
+                description,
+                width = width
+            ));
+        }
+    }
+    if let Some(after_help) = &command.after_help {
+        text.push_str(&String::from("\n"));
+        text.push_str(&format!("{}\n", after_help));
+    }
+
+    text
+}
+
+/// A type that supports for compact help rendering of a one-line usage
+///
+/// # Example
+///
+/// ```text
+/// Usage: command TEXT
+/// ```
+#[derive(Default)]
+pub struct CompactHelpRender {}
+
+impl CompactHelpRender {
+    /// Returns a `CompactHelpRender` object
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl HelpRender for CompactHelpRender {
+    fn help_text(&self, command: &Command) -> String {
+        format!("Usage: {}", command.usage_line())
+    }
+}
+
+/// A type that supports for help rendering from a template with placeholders
+///
+/// Placeholders are `{name}`, `{description}`, `{usage}`, `{flags}` and `{version}`, like the default help `{description}`
+/// expands to the long description when set and `{flags}` expands to the lines of flags and global flags not hidden
+/// from help, hidden flags included for `--help-all`, unknown placeholders like `{other}` are left literal
+///
+/// # Example
+///
+/// ```
+/// # use cancer::{command::Command, help::{HelpRender, TemplateHelpRender}};
+/// let command = Command::new("gives a friendly hello", "hello TEXT", |_, _, _| Ok(())).name("hello");
+/// let render = TemplateHelpRender::new("{name}: {description}\nUsage: {usage}");
+///
+/// assert_eq!("hello: gives a friendly hello\nUsage: hello TEXT", render.help_text(&command));
+/// ```
+pub struct TemplateHelpRender {
+    /// Template which placeholders are expanded in
+    template: String,
+}
+
+impl TemplateHelpRender {
+    /// Returns a `TemplateHelpRender` object
+    ///
+    /// # Arguments
+    ///
+    /// `template` - A string slice that holds the template with placeholders
+    pub fn new(template: &str) -> Self {
+        Self {
+            template: String::from(template),
         }
+    }
+
+    /// Returns the expansion of a placeholder, or `None` for an unknown placeholder
+    fn expand(&self, command: &Command, key: &str) -> Option<String> {
+        let value = match key {
+            "name" => command.name.clone(),
+            "description" => String::from(help_description(command)),
+            "usage" => command.usage_line(),
+            "version" => String::from(command.get_version()),
+            "flags" => {
+                let width = flags_width(command);
+                visible_flags(command, &command.flags)
+                    .into_iter()
+                    .chain(visible_flags(command, &command.global_flags))
+                    .map(|x| flag_text(command, x, width, false, None))
+                    .collect::<Vec<String>>()
+                    .join("\n")
+            }
+            _ => return None,
+        };
+
+        Some(value)
+    }
+}
+
+impl HelpRender for TemplateHelpRender {
+    fn help_text(&self, command: &Command) -> String {
+        let mut text = String::from("");
+        let mut rest = &self.template[..];
+        while let Some(start) = rest.find('{') {
+            text.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let expanded = rest
+                .find('}')
+                .and_then(|end| Some((end, self.expand(command, &rest[1..end])?)));
+            match expanded {
+                Some((end, value)) => {
+                    text.push_str(&value);
+                    rest = &rest[end + 1..];
+                }
+                None => {
+                    text.push('{');
+                    rest = &rest[1..];
+                }
+            }
+        }
+        text.push_str(rest);
 
         text
     }
 }
+
+/// Returns the long description of command in its own help, or the description when not set
+fn help_description(command: &Command) -> &str {
+    command
+        .long_description
+        .as_deref()
+        .unwrap_or(&command.description)
+}
+
+/// Returns flags in the order listed in help, without hidden flags unless help is rendered for `--help-all`
+fn visible_flags<'a>(command: &Command, flags: &'a [Flag]) -> Vec<&'a Flag> {
+    command
+        .help_order(flags)
+        .into_iter()
+        .filter(|x| !x.hidden || command.is_help_all())
+        .collect()
+}
+
+/// Returns the widest width of flag identifiers among flags and global flags of command
+fn flags_width(command: &Command) -> usize {
+    command
+        .flags
+        .iter()
+        .chain(command.global_flags.iter())
+        .filter(|x| !x.hidden || command.is_help_all())
+        .map(|x| {
+            x.names(&command.flag_short_start, &command.flag_long_start)
+                .chars()
+                .count()
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// Returns subcommands of command which are not hidden
+fn visible_subcommands(command: &Command) -> impl Iterator<Item = (&str, &Command)> {
+    command.subcommands().filter(|(_, x)| !x.is_hidden())
+}
+
+/// Returns the widest width of labels of subcommands not hidden
+fn subcommands_width(command: &Command) -> usize {
+    visible_subcommands(command)
+        .map(|(name, subcommand)| subcommand_label(name, subcommand).chars().count())
+        .max()
+        .unwrap_or(0)
+}
+
+/// Returns the name of a subcommand followed by its aliases in parentheses if any, e.g. `checkout (co)`
+fn subcommand_label(name: &str, subcommand: &Command) -> String {
+    if subcommand.aliases().is_empty() {
+        String::from(name)
+    } else {
+        format!("{} ({})", name, subcommand.aliases().join(", "))
+    }
+}
+
+/// Returns the help line of a flag with the flag prefixes of command, descriptions are aligned in a column
+///
+/// # Arguments
+///
+/// `command` - A `Command` that holds the flag
+///
+/// `flag` - A `Flag` to render
+///
+/// `width` - The width which flag identifiers are padded to
+///
+/// `color` - A bool that holds whether flag identifiers are colored by the command theme
+///
+/// `line_width` - The width in columns which the description is wrapped to, not wrapped when `None`
+fn flag_text(
+    command: &Command,
+    flag: &Flag,
+    width: usize,
+    color: bool,
+    line_width: Option<usize>,
+) -> String {
+    let mut description = flag.description.clone();
+    let default = flag.default.to_string();
+    if flag.default != FlagValue::Bool(false) && !default.is_empty() {
+        description.push_str(&format!(" [default: {}]", default));
+    }
+    if let Some(env) = command.flag_env(flag) {
+        description.push_str(&format!(" [env: {}]", env));
+    }
+    if command.is_help_all() {
+        if flag.hidden {
+            description.push_str(" (hidden)");
+        }
+        if let Some(message) = &flag.deprecated {
+            description.push_str(&format!(" (deprecated: {})", message));
+        }
+    }
+
+    let names = flag.names(&command.flag_short_start, &command.flag_long_start);
+    let padding = " ".repeat(width.saturating_sub(names.chars().count()));
+    let indent = width + 6;
+    let lines = match line_width {
+        Some(line_width) if line_width > indent => wrap(&description, line_width - indent),
+        _ => vec![description],
+    };
+    let mut text = format!(
+        "  {}{}    {}",
+        paint(&names, &command.get_theme().flag, color),
+        padding,
+        lines[0]
+    );
+    for line in lines.iter().skip(1) {
+        text.push_str(&format!("\n{}{}", " ".repeat(indent), line));
+    }
+
+    text
+}
+
+/// Returns the lines of text wrapped at spaces to the width in columns, a word wider than the width is kept whole
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = vec![];
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    lines.push(line);
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        theme::Theme,
+        util::{BOLD, RESET},
+        PKG_NAME,
+    };
+
+    #[test]
+    fn help_text_after_help() {
+        let command = Command::new("test description", "test TEXT", |_, _, _| Ok(()))
+            .after_help("see also: test-extra");
+        let text = DefaultHelpRender::new().help_text(&command);
+        assert!(text.ends_with("\n\nsee also: test-extra"));
+
+        let command = command.after_help("see also: test-extra\n");
+        let text = DefaultHelpRender::new().help_text(&command);
+        assert!(text.ends_with("\n\nsee also: test-extra\n"));
+    }
+
+    #[test]
+    fn help_text_before_help() {
+        let command = Command::new("test description", "test TEXT", |_, _, _| Ok(()))
+            .before_help("test banner");
+        let text = DefaultHelpRender::new().help_text(&command);
+        assert!(text.starts_with("test banner\n\ntest description\n"));
+    }
+
+    #[test]
+    fn help_text_without_after_help() {
+        let command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        let text = DefaultHelpRender::new().help_text(&command);
+        assert!(text.ends_with(&format!("  -v, --version    version for {}", PKG_NAME)));
+
+        let text = DefaultHelpRender::new()
+            .trim_trailing(false)
+            .help_text(&command);
+        assert!(text.ends_with(&format!("  -v, --version    version for {}\n", PKG_NAME)));
+    }
+
+    #[test]
+    fn compact_help_text() {
+        let command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        let text = CompactHelpRender::new().help_text(&command);
+        assert_eq!(text, "Usage: test TEXT");
+    }
+
+    #[test]
+    fn help_text_force_color() {
+        let command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        let text = DefaultHelpRender::new().help_text(&command);
+        let forced = DefaultHelpRender::new().force_color().help_text(&command);
+        assert!(!text.contains('\x1b'));
+        assert!(forced.contains(&format!("{}Usage:{}", BOLD, RESET)));
+    }
+
+    #[test]
+    fn help_text_theme() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.theme(Theme {
+            header: String::from("\x1b[34m"),
+            flag: String::from("\x1b[32m"),
+            ..Theme::default()
+        });
+        let text = DefaultHelpRender::new().force_color().help_text(&command);
+        assert!(text.contains(&format!("\x1b[34mUsage:{}\n", RESET)));
+        assert!(text.contains(&format!("  \x1b[32m-h, --help{}       ", RESET)));
+        assert!(!text.contains(BOLD));
+
+        command.theme(Theme::no_color());
+        let text = DefaultHelpRender::new().force_color().help_text(&command);
+        assert!(!text.contains('\x1b'));
+    }
+
+    #[test]
+    fn catalog_help_text() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        let mut catalog = HashMap::new();
+        catalog.insert(String::from("flags_header"), String::from("Optionen:"));
+        let render = CatalogHelpRender::new(HashMap::new()).locale_catalog("de", catalog);
+
+        let text = render.help_text(&command);
+        assert!(text.contains("\nFlags:\n"));
+
+        command.locale("de");
+        let text = render.help_text(&command);
+        assert!(text.contains("\nOptionen:\n"));
+        assert!(!text.contains("Flags:"));
+        assert!(text.contains("\nUsage:\n"));
+        assert!(!text.ends_with('\n'));
+
+        let text = CatalogHelpRender::new(HashMap::new())
+            .trim_trailing(false)
+            .help_text(&command);
+        assert!(text.ends_with(&format!("  -v, --version    version for {}\n", PKG_NAME)));
+    }
+
+    #[test]
+    fn help_text_late_description_usage() {
+        let command = Command::new("test description", "test TEXT", |_, _, _| Ok(()))
+            .description("late description")
+            .usage("test [NAME]");
+        let text = DefaultHelpRender::new().help_text(&command);
+        assert!(text.starts_with("late description\n\nUsage:\n  test [NAME]\n"));
+        assert!(!text.contains("test description"));
+    }
+
+    #[test]
+    fn help_text_long_description() {
+        let subcommand = Command::new("sub description", "TEXT", |_, _, _| Ok(()))
+            .long_description("sub long description");
+        let mut command = Command::new("test description", "test COMMAND", |_, _, _| Ok(()));
+        command.add_subcommand("sub", subcommand);
+
+        let text = DefaultHelpRender::new().help_text(&command);
+        assert!(text.contains("  sub    sub description\n"));
+        assert!(!text.contains("sub long description"));
+
+        let (_, subcommand) = command.subcommands().next().unwrap();
+        let text = DefaultHelpRender::new().help_text(subcommand);
+        assert!(text.starts_with("sub long description\n"));
+    }
+
+    #[test]
+    fn help_text_hidden_flag() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.add_flag(Flag::new_bool("", "colour", "test hidden").hidden());
+        let text = DefaultHelpRender::new().help_text(&command);
+        assert!(!text.contains("test hidden"));
+    }
+
+    #[test]
+    fn help_text_subcommand_aliases() {
+        let mut command = Command::new("test description", "test COMMAND", |_, _, _| Ok(()));
+        let mut checkout = Command::new("checkout description", "BRANCH", |_, _, _| Ok(()));
+        checkout.add_alias("co");
+        command.add_subcommand("checkout", checkout);
+        command.add_subcommand("log", Command::new("log description", "", |_, _, _| Ok(())));
+        let text = DefaultHelpRender::new().help_text(&command);
+        assert!(text.contains("\n  checkout (co)    checkout description\n"));
+        assert!(text.contains("\n  log              log description\n"));
+    }
+
+    #[test]
+    fn help_text_hidden_subcommand() {
+        let mut command = Command::new("test description", "test COMMAND", |_, _, _| Ok(()));
+        command.add_subcommand("log", Command::new("log description", "", |_, _, _| Ok(())));
+        command.add_subcommand(
+            "status",
+            Command::new("status description", "", |_, _, _| Ok(())),
+        );
+        let mut debug = Command::new("debug description", "", |_, _, _| Ok(()));
+        debug.hidden();
+        command.add_subcommand("debug-internals", debug);
+        let text = DefaultHelpRender::new().help_text(&command);
+        assert!(text.contains(
+            "Commands:\n  log       log description\n  status    status description\n\n"
+        ));
+        assert!(!text.contains("debug"));
+
+        let mut command = Command::new("test description", "test COMMAND", |_, _, _| Ok(()));
+        let mut debug = Command::new("debug description", "", |_, _, _| Ok(()));
+        debug.hidden();
+        command.add_subcommand("debug", debug);
+        let text = DefaultHelpRender::new().help_text(&command);
+        assert!(!text.contains("Commands:"));
+    }
+
+    #[test]
+    fn help_text_examples() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        let text = DefaultHelpRender::new().help_text(&command);
+        assert!(!text.contains("Examples:"));
+
+        command.add_example("test -n world", "test example");
+        command.add_example("test --help", "test help");
+        let text = DefaultHelpRender::new().help_text(&command);
+        assert!(text.ends_with(
+            "\nExamples:\n  test -n world    test example\n  test --help      test help"
+        ));
+    }
+
+    #[test]
+    fn help_text_flag_hints() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.env_prefix("TEST");
+        command.add_flag(
+            Flag::new_int("p", "port", "test int")
+                .default_value(FlagValue::Int(Some(8080)))
+                .env("APP_PORT"),
+        );
+        command.add_flag(Flag::new_bool("x", "xray", "test bool"));
+        let text = DefaultHelpRender::new().help_text(&command);
+        assert!(text
+            .lines()
+            .any(|x| x.ends_with("test int [default: 8080] [env: APP_PORT]")));
+        assert!(text
+            .lines()
+            .any(|x| x.ends_with("test bool [env: TEST_XRAY]")));
+        assert!(!text.contains("TEST_HELP"));
+    }
+
+    #[test]
+    fn help_text_sorted_flags() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.add_flag(Flag::new_bool("z", "zulu", "test z"));
+        command.add_flag(Flag::new_bool("a", "alpha", "test a"));
+        command.add_flag(Flag::new_bool("m", "", "test m"));
+        let position = |text: &str, name: &str| text.find(name).unwrap();
+
+        let text = DefaultHelpRender::new().help_text(&command);
+        assert!(position(&text, "--help") < position(&text, "--zulu"));
+        assert!(position(&text, "--zulu") < position(&text, "--alpha"));
+
+        command.sort_flags(true);
+        let text = DefaultHelpRender::new().help_text(&command);
+        assert!(position(&text, "--alpha") < position(&text, "test m"));
+        assert!(position(&text, "test m") < position(&text, "--zulu"));
+        assert!(position(&text, "--zulu") < position(&text, "--help"));
+        assert!(position(&text, "--help") < position(&text, "--version"));
+    }
+
+    #[test]
+    fn help_text_negatable_flag() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.add_flag(Flag::new_bool("c", "color", "test negatable").negatable());
+        command.add_flag(Flag::new_bool("x", "xray", "test bool"));
+        let text = DefaultHelpRender::new().help_text(&command);
+        assert!(text.contains("-c, --[no-]color"));
+        assert!(text.contains("-x, --xray "));
+        assert!(!text.contains("[no-]xray"));
+    }
+
+    #[test]
+    fn help_text_single_identifier_flags() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.add_flag(Flag::new_long(
+            "long-only",
+            "test long",
+            FlagValue::Bool(false),
+        ));
+        command.add_flag(Flag::new_short("s", "test short", FlagValue::Bool(false)));
+        let text = DefaultHelpRender::new().help_text(&command);
+        assert!(text.contains("\n  --long-only    "));
+        assert!(text.contains("\n  -s    "));
+        assert!(!text.contains(", --\n") && !text.contains("-, "));
+    }
+
+    #[test]
+    fn help_text_wrapped_flags() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.add_flag(Flag::new_bool(
+            "x",
+            "xray",
+            "test a long description which is wrapped to the width",
+        ));
+        let text = DefaultHelpRender::new().width(40).help_text(&command);
+        assert!(text.lines().all(|x| x.chars().count() <= 40));
+        assert!(text.contains("\n  -x, --xray       test a long\n"));
+        assert!(text.contains("\n                   description which is\n"));
+        assert!(text.contains("\n                   wrapped to the width"));
+
+        let text = DefaultHelpRender::new().width(200).help_text(&command);
+        assert!(text.contains("test a long description which is wrapped to the width"));
+    }
+
+    #[test]
+    fn help_text_aligned_flags() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.add_flag(Flag::new_bool("a", "a", "test short"));
+        command.add_flag(Flag::new_bool("l", "a-much-longer-name", "test long"));
+        let text = DefaultHelpRender::new().help_text(&command);
+        let short = text.lines().find(|x| x.contains("test short")).unwrap();
+        let long = text.lines().find(|x| x.contains("test long")).unwrap();
+        assert_eq!(short.find("test short"), long.find("test long"));
+        assert!(!text.contains('\t'));
+    }
+
+    #[test]
+    fn template_help_text() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()))
+            .name("test")
+            .version_string_value("1.2.3");
+        command.add_flag(Flag::new_bool("x", "xray", "test bool"));
+        let render =
+            TemplateHelpRender::new("{flags}\n\n{usage} | {name} {version}\n{description} {other}");
+        let text = render.help_text(&command);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 6);
+        assert!(lines[0].starts_with("  -h, --help "));
+        assert!(lines[2].starts_with("  -x, --xray "));
+        assert!(lines[2].ends_with("test bool"));
+        assert_eq!(lines[4], "test TEXT | test 1.2.3");
+        assert_eq!(lines[5], "test description {other}");
+
+        let mut command = command.long_description("test long description");
+        command.add_flag(Flag::new_bool("", "debug", "test hidden").hidden());
+        let text = TemplateHelpRender::new("{description}\n{flags}").help_text(&command);
+        assert!(text.starts_with("test long description\n"));
+        assert!(!text.contains("--debug"));
+    }
+}
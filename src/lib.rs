@@ -1,10 +1,18 @@
 pub mod command;
+pub mod completion;
+pub mod config;
+pub mod error;
 pub mod flag;
 pub mod help;
+pub mod theme;
 pub mod util;
 pub mod version;
 
 use std::env;
 
+/// Derive macro of `flag::FromFlags`, available with the `derive` feature
+#[cfg(feature = "derive")]
+pub use cancer_derive::Command;
+
 pub const PKG_NAME: &str = env!("CARGO_PKG_NAME");
 pub const PKG_VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -0,0 +1,54 @@
+use crate::util::{BOLD, RED};
+
+/// A type that holds the ANSI escape codes of colors used by help and errors, an empty code is not colored
+///
+/// Colors are only used when color is enabled, e.g. by `DefaultHelpRender::color` or `Command::error_color`
+///
+/// # Example
+///
+/// ```
+/// # use cancer::theme::Theme;
+/// let theme = Theme {
+///     header: String::from("\x1b[1;34m"),
+///     ..Theme::default()
+/// };
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Theme {
+    /// Color of section headings in help, bold by default
+    pub header: String,
+    /// Color of flag identifiers in help, not colored by default
+    pub flag: String,
+    /// Color of errors, red by default
+    pub error: String,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            header: String::from(BOLD),
+            flag: String::new(),
+            error: String::from(RED),
+        }
+    }
+}
+
+impl Theme {
+    /// Returns a theme without any color, so that nothing is colored even when color is enabled
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cancer::theme::Theme;
+    /// let theme = Theme::no_color();
+    ///
+    /// assert!(theme.error.is_empty());
+    /// ```
+    pub fn no_color() -> Self {
+        Self {
+            header: String::new(),
+            flag: String::new(),
+            error: String::new(),
+        }
+    }
+}
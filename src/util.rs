@@ -1,6 +1,381 @@
-use std::env;
+use std::{
+    env,
+    io::{self, IsTerminal},
+};
+
+use crate::PKG_NAME;
+
+/// ANSI escape code of bold text
+pub const BOLD: &str = "\x1b[1m";
+/// ANSI escape code of red text
+pub const RED: &str = "\x1b[31m";
+/// ANSI escape code which resets text style
+pub const RESET: &str = "\x1b[0m";
+/// Width of a terminal whose width is unknown
+const DEFAULT_TERM_WIDTH: usize = 80;
 
 /// Returns a vector of string that holds args from env
 pub fn get_args() -> Vec<String> {
     env::args().collect()
 }
+
+/// Returns the program name invoked, which is the basename of the first arg without a `.exe` suffix,
+/// e.g. `hello` for `/usr/bin/hello` or `C:\bin\hello.exe`, the package name when args are empty
+///
+/// # Arguments
+///
+/// `args` - A slice of string that holds args, including the program path
+///
+/// # Examples
+///
+/// ```
+/// # use cancer::util::program_name;
+/// assert_eq!("hello", program_name(&[String::from("/usr/bin/hello")]));
+/// ```
+pub fn program_name(args: &[String]) -> String {
+    let program = match args.first() {
+        Some(program) => program,
+        None => return String::from(PKG_NAME),
+    };
+    let name = program.rsplit(['/', '\\']).next().unwrap_or(program);
+    let name = match name.len().checked_sub(4) {
+        Some(end) if name.is_char_boundary(end) && name[end..].eq_ignore_ascii_case(".exe") => {
+            &name[..end]
+        }
+        _ => name,
+    };
+
+    String::from(name)
+}
+
+/// Returns if color is used, color is suppressed when the `NO_COLOR` environment variable is present unless forced
+///
+/// # Arguments
+///
+/// `enabled` - A bool that holds whether color is enabled
+///
+/// `forced` - A bool that holds whether color is used regardless of `NO_COLOR`
+pub fn use_color(enabled: bool, forced: bool) -> bool {
+    use_color_with(enabled, forced, env::var_os("NO_COLOR").is_some())
+}
+
+/// Returns if color is used like `use_color`, with the presence of `NO_COLOR` provided instead of looked up
+///
+/// # Arguments
+///
+/// `enabled` - A bool that holds whether color is enabled
+///
+/// `forced` - A bool that holds whether color is used regardless of `NO_COLOR`
+///
+/// `no_color` - A bool that holds whether the `NO_COLOR` environment variable is present
+///
+/// # Examples
+///
+/// ```
+/// # use cancer::util::use_color_with;
+/// assert_eq!(false, use_color_with(true, false, true));
+/// assert_eq!(true, use_color_with(true, true, true));
+/// ```
+pub fn use_color_with(enabled: bool, forced: bool, no_color: bool) -> bool {
+    forced || (enabled && !no_color)
+}
+
+/// Returns the text wrapped in an ANSI style when `color` is true and the style is not empty
+///
+/// # Arguments
+///
+/// `text` - A string slice that holds the text
+///
+/// `style` - A string slice that holds the ANSI escape code of the style, e.g. `RED`
+///
+/// `color` - A bool that holds whether the style is applied
+///
+/// # Examples
+///
+/// ```
+/// # use cancer::util::{paint, RED, RESET};
+/// assert_eq!("\x1b[31merror\x1b[0m", paint("error", RED, true));
+/// assert_eq!("error", paint("error", RED, false));
+/// ```
+pub fn paint(text: &str, style: &str, color: bool) -> String {
+    if color && !style.is_empty() {
+        format!("{}{}{}", style, text, RESET)
+    } else {
+        String::from(text)
+    }
+}
+
+/// Returns the width of the terminal in columns, read from the `COLUMNS` environment variable,
+/// `80` when it is not set and `stdout` is a terminal, `None` when output is not a terminal
+///
+/// # Examples
+///
+/// ```
+/// # use cancer::util::term_width;
+/// let width = term_width().unwrap_or(80);
+/// ```
+pub fn term_width() -> Option<usize> {
+    columns_width(
+        env::var("COLUMNS").ok().as_deref(),
+        io::stdout().is_terminal(),
+    )
+}
+
+/// Returns the terminal width from the value of `COLUMNS` if valid, otherwise the default width for a terminal
+fn columns_width(columns: Option<&str>, is_terminal: bool) -> Option<usize> {
+    match columns.and_then(|x| x.trim().parse().ok()) {
+        Some(width) if width > 0 => Some(width),
+        _ if is_terminal => Some(DEFAULT_TERM_WIDTH),
+        _ => None,
+    }
+}
+
+/// Returns the Levenshtein edit distance between two strings, counted in characters
+///
+/// # Arguments
+///
+/// `a` - A string slice that holds the first string
+///
+/// `b` - A string slice that holds the second string
+///
+/// # Examples
+///
+/// ```
+/// # use cancer::util::levenshtein;
+/// let distance = levenshtein("verbos", "verbose");
+///
+/// assert_eq!(1, distance);
+/// ```
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut distances: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut previous = distances[0];
+        distances[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous + usize::from(a_char != *b_char);
+            previous = distances[j + 1];
+            distances[j + 1] = substitution.min(distances[j] + 1).min(previous + 1);
+        }
+    }
+
+    distances[b.len()]
+}
+
+/// Returns the bool spelled by the text, `true`, `false`, `yes`, `no`, `on`, `off`, `y`, `n`, `1` and `0`
+/// are accepted case-insensitively
+///
+/// # Arguments
+///
+/// `s` - A string slice that holds the text
+///
+/// # Examples
+///
+/// ```
+/// # use cancer::util::parse_bool;
+/// assert_eq!(Some(true), parse_bool("Yes"));
+/// assert_eq!(Some(false), parse_bool("off"));
+/// assert_eq!(None, parse_bool("maybe"));
+/// ```
+pub fn parse_bool(s: &str) -> Option<bool> {
+    match &s.to_lowercase()[..] {
+        "true" | "yes" | "on" | "y" | "1" => Some(true),
+        "false" | "no" | "off" | "n" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// Returns a text with accents stripped and case preserved, e.g. `Cafe` for `Café`
+///
+/// Combining accents are dropped and accented Latin-1 letters are replaced by their base letters,
+/// other characters are kept as is
+///
+/// # Arguments
+///
+/// `text` - A string slice that holds the text, either composed or decomposed
+///
+/// # Examples
+///
+/// ```
+/// # use cancer::util::strip_accents;
+/// assert_eq!("cafe", strip_accents("caf\u{e9}"));
+/// assert_eq!("cafe", strip_accents("cafe\u{301}"));
+/// ```
+pub fn strip_accents(text: &str) -> String {
+    text.chars()
+        .filter(|c| !('\u{300}'..='\u{36f}').contains(c))
+        .map(|c| match c {
+            'À'..='Å' => 'A',
+            'Ç' => 'C',
+            'È'..='Ë' => 'E',
+            'Ì'..='Ï' => 'I',
+            'Ñ' => 'N',
+            'Ò'..='Ö' | 'Ø' => 'O',
+            'Ù'..='Ü' => 'U',
+            'Ý' => 'Y',
+            'à'..='å' => 'a',
+            'ç' => 'c',
+            'è'..='ë' => 'e',
+            'ì'..='ï' => 'i',
+            'ñ' => 'n',
+            'ò'..='ö' | 'ø' => 'o',
+            'ù'..='ü' => 'u',
+            'ý' | 'ÿ' => 'y',
+            c => c,
+        })
+        .collect()
+}
+
+/// Returns a text quoted as a JSON string, with quotes, backslashes and control characters escaped
+///
+/// # Arguments
+///
+/// `text` - A string slice that holds the text
+///
+/// # Examples
+///
+/// ```
+/// # use cancer::util::json_string;
+/// assert_eq!(r#""say \"hi\"\n""#, json_string("say \"hi\"\n"));
+/// ```
+pub fn json_string(text: &str) -> String {
+    let mut json = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+
+    json
+}
+
+/// Returns a camelCase text converted to kebab-case, acronyms are kept as one word
+///
+/// # Arguments
+///
+/// `text` - A string slice that holds the camelCase text
+///
+/// # Examples
+///
+/// ```
+/// # use cancer::util::kebab_case;
+/// assert_eq!("max-size", kebab_case("maxSize"));
+/// assert_eq!("max-http-size", kebab_case("maxHTTPSize"));
+/// ```
+pub fn kebab_case(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut kebab = String::new();
+
+    for (i, c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let previous = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(|x| x.is_lowercase());
+            if previous.is_lowercase()
+                || previous.is_ascii_digit()
+                || (previous.is_uppercase() && next_lower)
+            {
+                kebab.push('-');
+            }
+        }
+        kebab.extend(c.to_lowercase());
+    }
+
+    kebab
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn use_color_with_no_color() {
+        assert!(use_color_with(true, false, false));
+        assert!(!use_color_with(true, false, true));
+        assert!(!use_color_with(false, false, false));
+        assert!(use_color_with(false, true, true));
+    }
+
+    #[test]
+    fn parse_bool_accepted() {
+        for s in ["true", "TRUE", "yes", "Yes", "on", "ON", "y", "Y", "1"] {
+            assert_eq!(parse_bool(s), Some(true), "{}", s);
+        }
+        for s in ["false", "False", "no", "NO", "off", "Off", "n", "N", "0"] {
+            assert_eq!(parse_bool(s), Some(false), "{}", s);
+        }
+    }
+
+    #[test]
+    fn parse_bool_rejected() {
+        for s in ["", "maybe", "2", "yess", " true"] {
+            assert_eq!(parse_bool(s), None, "{}", s);
+        }
+    }
+
+    #[test]
+    fn columns_width() {
+        assert_eq!(super::columns_width(Some("40"), false), Some(40));
+        assert_eq!(super::columns_width(Some("40"), true), Some(40));
+        assert_eq!(super::columns_width(Some("0"), true), Some(80));
+        assert_eq!(super::columns_width(Some("wide"), false), None);
+        assert_eq!(super::columns_width(None, true), Some(80));
+        assert_eq!(super::columns_width(None, false), None);
+    }
+
+    #[test]
+    fn levenshtein_same() {
+        assert_eq!(levenshtein("verbose", "verbose"), 0);
+        assert_eq!(levenshtein("", ""), 0);
+    }
+
+    #[test]
+    fn levenshtein_edits() {
+        assert_eq!(levenshtein("verbos", "verbose"), 1);
+        assert_eq!(levenshtein("verbsoe", "verbose"), 2);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("", "help"), 4);
+        assert_eq!(levenshtein("café", "cafe"), 1);
+    }
+
+    #[test]
+    fn strip_accents() {
+        assert_eq!(super::strip_accents("Café"), "Cafe");
+        assert_eq!(super::strip_accents("cafe\u{301}"), "cafe");
+        assert_eq!(super::strip_accents("naïve-Øre"), "naive-Ore");
+        assert_eq!(super::strip_accents("größe"), "große");
+        assert_eq!(super::strip_accents("max-size"), "max-size");
+    }
+
+    #[test]
+    fn json_string() {
+        assert_eq!(super::json_string("name"), r#""name""#);
+        assert_eq!(super::json_string("a \"b\" \\ c"), r#""a \"b\" \\ c""#);
+        assert_eq!(super::json_string("a\tb\u{1}"), r#""a\tb\u0001""#);
+    }
+
+    #[test]
+    fn kebab_case() {
+        assert_eq!(super::kebab_case("maxSize"), "max-size");
+        assert_eq!(super::kebab_case("maxHTTP"), "max-http");
+        assert_eq!(super::kebab_case("HTTPServer"), "http-server");
+        assert_eq!(super::kebab_case("max2Size"), "max2-size");
+        assert_eq!(super::kebab_case("max-size"), "max-size");
+    }
+
+    #[test]
+    fn program_name() {
+        let name = |arg: &str| super::program_name(&[String::from(arg)]);
+        assert_eq!(name("/usr/local/bin/hello"), "hello");
+        assert_eq!(name("C:\\Program Files\\hello\\hello.EXE"), "hello");
+        assert_eq!(name("hello"), "hello");
+        assert_eq!(super::program_name(&[]), PKG_NAME);
+    }
+}
@@ -1,12 +1,15 @@
-use crate::{command::Command, PKG_NAME, PKG_VERSION};
+use crate::{command::Command, flag::FlagValue};
+
+const VERBOSE_LONG: &str = "verbose";
 
 /// A trait that supports for version rendering
 ///
 /// # Example
 ///
 /// ```
+/// # use cancer::{command::Command, version::VersionRender};
 /// pub struct CustomVersionRender {}
-
+///
 /// impl VersionRender for CustomVersionRender {
 ///     fn version_text(&self, command: &Command) -> String {
 ///         format!("my custom version")
@@ -22,11 +25,22 @@ pub trait VersionRender {
     fn version_text(&self, command: &Command) -> String;
 }
 
-/// A type that supports for default version rendering
+/// A type that supports for default version rendering, the version set by `Command::version_string_value`
+/// is preferred over the crate version
 ///
 /// # Example
 ///
+/// ```text
 /// command version 1.0.0
+/// ```
+///
+/// Extra version lines of the command are appended when the `verbose` flag of the command is set
+///
+/// ```text
+/// command version 1.0.0
+/// ferris-says 0.2.1
+/// ```
+#[derive(Default)]
 pub struct DefaultVersionRender {}
 
 impl DefaultVersionRender {
@@ -37,7 +51,88 @@ impl DefaultVersionRender {
 }
 
 impl VersionRender for DefaultVersionRender {
-    fn version_text(&self, _command: &Command) -> String {
-        format!("{} version {}", PKG_NAME, PKG_VERSION)
+    fn version_text(&self, command: &Command) -> String {
+        let mut text = format!("{} version {}", command.name, command.get_version());
+        let verbose = command
+            .flags
+            .iter()
+            .any(|x| x.long == VERBOSE_LONG && x.value == FlagValue::Bool(true));
+        if verbose {
+            for line in command.version_lines() {
+                text.push_str(&format!("\n{}", line));
+            }
+        }
+
+        text
+    }
+}
+
+/// A type that supports for version rendering of the raw version number only, easy to capture in scripts
+///
+/// # Example
+///
+/// ```text
+/// 1.0.0
+/// ```
+#[derive(Default)]
+pub struct RawVersionRender {}
+
+impl RawVersionRender {
+    /// Returns a `RawVersionRender` object
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl VersionRender for RawVersionRender {
+    fn version_text(&self, command: &Command) -> String {
+        String::from(command.get_version())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{flag::Flag, PKG_NAME, PKG_VERSION};
+
+    #[test]
+    fn version_text_verbose_lines() {
+        let mut command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        command.add_flag(Flag::new_bool("V", "verbose", "test verbose"));
+        command.add_version_line("dep 1.0.0");
+        let text = DefaultVersionRender::new().version_text(&command);
+        assert_eq!(text, format!("{} version {}", PKG_NAME, PKG_VERSION));
+
+        command.flags[2].value = FlagValue::Bool(true);
+        let text = DefaultVersionRender::new().version_text(&command);
+        assert_eq!(
+            text,
+            format!("{} version {}\ndep 1.0.0", PKG_NAME, PKG_VERSION)
+        );
+    }
+
+    #[test]
+    fn raw_version_text() {
+        let command = Command::new("test description", "test TEXT", |_, _, _| Ok(()));
+        let text = RawVersionRender::new().version_text(&command);
+        assert_eq!(text, PKG_VERSION);
+    }
+
+    #[test]
+    fn version_text_name() {
+        let command = Command::new("test description", "test TEXT", |_, _, _| Ok(())).name("hello");
+        let text = DefaultVersionRender::new().version_text(&command);
+        assert_eq!(text, format!("hello version {}", PKG_VERSION));
+    }
+
+    #[test]
+    fn version_text_override() {
+        let command = Command::new("test description", "test TEXT", |_, _, _| Ok(()))
+            .name("hello")
+            .version_string_value("9.8.7");
+        let text = DefaultVersionRender::new().version_text(&command);
+        assert_eq!(text, "hello version 9.8.7");
+        assert!(!text.contains(PKG_VERSION));
+        assert_eq!(RawVersionRender::new().version_text(&command), "9.8.7");
     }
 }